@@ -0,0 +1,291 @@
+use crate::config::write_atomic;
+use crate::networks::{Network, is_address};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Labelled addresses (`addresses.toml` next to the config). Labels under
+/// `[networks.<name>]` win over `[global]` ones on that network.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AddressBook {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub global: BTreeMap<String, String>,
+    /// Keyed by canonical network name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Labels are case-insensitive and stored lowercase: a letter, then letters,
+/// digits, `_` or `-`. Starting with a letter keeps them apart from block numbers
+/// and hex values in `explorer`.
+pub fn normalize_label(label: &str) -> Result<String, String> {
+    let label = label.to_lowercase();
+    let mut chars = label.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!(
+            "Invalid label '{}'. Labels start with a letter and contain only letters, digits, '_' and '-'.",
+            label
+        ));
+    }
+    Ok(label)
+}
+
+/// `usdc` → `ADDR_USDC`, `usdc-e` → `ADDR_USDC_E`.
+pub fn env_var(label: &str) -> String {
+    format!("ADDR_{}", label.to_uppercase().replace('-', "_"))
+}
+
+/// Space-separated `ADDR_<LABEL>` names exported by the last switch, so the next
+/// switch and `off` unset exactly those.
+pub const TRACKING_VAR: &str = "STARGATE_ADDR_VARS";
+
+/// The names listed in a `TRACKING_VAR` value. Anything that isn't an `ADDR_<LABEL>`
+/// name is dropped, since the names end up in `unset` statements the shell evals.
+pub fn tracked_vars(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .filter(|name| {
+            name.strip_prefix("ADDR_").is_some_and(|rest| {
+                !rest.is_empty()
+                    && rest
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            })
+        })
+        .map(String::from)
+        .collect()
+}
+
+impl AddressBook {
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|d| d.join("addresses.toml"))
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not determine home directory")?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize address book: {}", e))?;
+        write_atomic(&path, &content).map_err(|e| format!("Failed to write address book: {}", e))
+    }
+
+    /// Adds or replaces `label`, globally or for one network. Returns the
+    /// normalized label and the address it replaced, if any. Labels that differ
+    /// but share an `ADDR_<LABEL>` variable (`usdc-e`, `usdc_e`) are rejected.
+    pub fn add(
+        &mut self,
+        label: &str,
+        address: &str,
+        network: Option<&Network>,
+    ) -> Result<(String, Option<String>), String> {
+        let label = normalize_label(label)?;
+        if !is_address(address) {
+            return Err(format!(
+                "'{}' is not an address (expected 0x followed by 40 hex digits).",
+                address
+            ));
+        }
+
+        let var = env_var(&label);
+        if let Some(other) = self
+            .all_labels()
+            .into_iter()
+            .find(|other| *other != label && env_var(other) == var)
+        {
+            return Err(format!(
+                "Label '{}' would share ${} with the existing label '{}'. Use '{}' or pick another label.",
+                label, var, other, other
+            ));
+        }
+
+        let labels = match network {
+            Some(network) => self.networks.entry(network.name.to_string()).or_default(),
+            None => &mut self.global,
+        };
+        let replaced = labels.insert(label.clone(), address.to_string());
+        Ok((label, replaced))
+    }
+
+    /// Removes `label` from the global labels or one network's; returns the address.
+    pub fn remove(&mut self, label: &str, network: Option<&Network>) -> Option<String> {
+        let label = label.to_lowercase();
+        match network {
+            Some(network) => {
+                let labels = self.networks.get_mut(network.name)?;
+                let removed = labels.remove(&label);
+                if labels.is_empty() {
+                    self.networks.remove(network.name);
+                }
+                removed
+            }
+            None => self.global.remove(&label),
+        }
+    }
+
+    /// The address `label` stands for on `network`, falling back to global labels.
+    pub fn resolve(&self, label: &str, network: Option<&Network>) -> Option<&str> {
+        let label = label.to_lowercase();
+        network
+            .and_then(|n| self.networks.get(n.name))
+            .and_then(|labels| labels.get(&label))
+            .or_else(|| self.global.get(&label))
+            .map(String::as_str)
+    }
+
+    /// Every label usable on `network` (global ones included), sorted.
+    pub fn labels_for(&self, network: Option<&Network>) -> BTreeMap<&str, &str> {
+        let mut labels: BTreeMap<&str, &str> = self
+            .global
+            .iter()
+            .map(|(label, address)| (label.as_str(), address.as_str()))
+            .collect();
+        if let Some(own) = network.and_then(|n| self.networks.get(n.name)) {
+            labels.extend(own.iter().map(|(l, a)| (l.as_str(), a.as_str())));
+        }
+        labels
+    }
+
+    /// Every label defined anywhere in the book, sorted and deduplicated.
+    pub fn all_labels(&self) -> Vec<&str> {
+        let mut labels: Vec<&str> = self
+            .global
+            .keys()
+            .chain(self.networks.values().flat_map(|labels| labels.keys()))
+            .map(String::as_str)
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
+    /// `ADDR_<LABEL>` variables for the labels usable on `network`.
+    pub fn env_vars(&self, network: Option<&Network>) -> Vec<(String, String)> {
+        self.labels_for(network)
+            .into_iter()
+            .map(|(label, address)| (env_var(label), address.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::find_network;
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const USDC_BASE: &str = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
+    const SAFE: &str = "0x00000000000000000000000000000000000000aa";
+
+    fn book() -> AddressBook {
+        let mut book = AddressBook::default();
+        book.add("safe", SAFE, None).unwrap();
+        book.add("USDC", USDC, find_network("mainnet")).unwrap();
+        book.add("usdc", USDC_BASE, find_network("base")).unwrap();
+        book
+    }
+
+    #[test]
+    fn labels_are_validated_and_lowercased() {
+        assert_eq!(normalize_label("USDC-e").unwrap(), "usdc-e");
+        assert!(normalize_label("1inch").is_err());
+        assert!(normalize_label("0xabc").is_err());
+        assert!(normalize_label("my label").is_err());
+        assert!(normalize_label("").is_err());
+
+        let mut book = AddressBook::default();
+        assert!(book.add("usdc", "0x1234", None).is_err());
+    }
+
+    #[test]
+    fn network_labels_win_over_global_ones() {
+        let mut book = book();
+        let mainnet = find_network("mainnet");
+        let base = find_network("base");
+        let arbitrum = find_network("arbitrum");
+
+        assert_eq!(book.resolve("usdc", mainnet), Some(USDC));
+        assert_eq!(book.resolve("USDC", base), Some(USDC_BASE));
+        assert_eq!(book.resolve("usdc", arbitrum), None);
+        assert_eq!(book.resolve("safe", arbitrum), Some(SAFE));
+
+        book.add("safe", USDC, base).unwrap();
+        assert_eq!(book.resolve("safe", base), Some(USDC));
+        assert_eq!(book.resolve("safe", None), Some(SAFE));
+    }
+
+    #[test]
+    fn removing_the_last_network_label_drops_the_section() {
+        let mut book = book();
+        let base = find_network("base");
+
+        assert_eq!(book.remove("usdc", base).as_deref(), Some(USDC_BASE));
+        assert!(!book.networks.contains_key("base"));
+        assert_eq!(book.remove("usdc", base), None);
+        assert_eq!(book.remove("usdc", None), None);
+    }
+
+    #[test]
+    fn env_vars_cover_labels_on_the_network() {
+        let book = book();
+        assert_eq!(
+            book.env_vars(find_network("arbitrum")),
+            vec![("ADDR_SAFE".to_string(), SAFE.to_string())]
+        );
+        assert_eq!(book.env_vars(find_network("base")).len(), 2);
+        assert_eq!(env_var("usdc-e"), "ADDR_USDC_E");
+    }
+
+    #[test]
+    fn labels_sharing_a_variable_are_rejected() {
+        let mut book = book();
+        book.add("usdc-e", USDC, find_network("base")).unwrap();
+
+        let err = book.add("USDC_E", USDC, None).unwrap_err();
+        assert!(
+            err.contains("share $ADDR_USDC_E with the existing label 'usdc-e'"),
+            "{}",
+            err
+        );
+        book.add("usdc-e", USDC_BASE, None).unwrap();
+    }
+
+    #[test]
+    fn tracked_vars_keep_only_address_variables() {
+        assert_eq!(
+            tracked_vars(" ADDR_SAFE  ADDR_USDC_E\n"),
+            vec!["ADDR_SAFE", "ADDR_USDC_E"]
+        );
+        assert!(tracked_vars("PATH ADDR_ ADDR_X;rm addr_y $(x)").is_empty());
+    }
+
+    #[test]
+    fn book_roundtrips_through_toml() {
+        let book = book();
+        let content = toml::to_string_pretty(&book).unwrap();
+        assert!(content.contains("[networks.base]"));
+        assert_eq!(toml::from_str::<AddressBook>(&content).unwrap(), book);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::addresses::{AddressBook, env_var, normalize_label};
+use crate::commands::env::{EnvFormat, render_vars};
+use crate::commands::exit_with;
+use crate::commands::list::render_table;
+use crate::networks::{Network, find_network, unknown_network_message};
+use crate::output::{OutputFormat, print_serialized};
+use serde::Serialize;
+use std::env;
+
+fn load_book() -> AddressBook {
+    AddressBook::load().unwrap_or_else(|e| exit_with(e))
+}
+
+fn save_book(book: &AddressBook) {
+    if let Err(e) = book.save() {
+        exit_with(format!("Failed to save address book: {}", e));
+    }
+}
+
+/// Resolves `--network`, exiting with suggestions on unknown names.
+fn network_arg(name: Option<&str>) -> Option<&'static Network> {
+    let name = name?;
+    match find_network(name) {
+        Some(network) => Some(network),
+        None => {
+            eprintln!("{}", unknown_network_message(name));
+            eprintln!("Run 'stargate list' to see available networks.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `--network`, else the active network.
+fn network_or_current(name: Option<&str>) -> Option<&'static Network> {
+    match name {
+        Some(name) => network_arg(Some(name)),
+        None => env::var("STARGATE_NETWORK")
+            .ok()
+            .and_then(|n| find_network(&n)),
+    }
+}
+
+fn scope(network: Option<&Network>) -> String {
+    network.map_or("global".to_string(), |n| n.name.to_string())
+}
+
+pub fn add(label: &str, address: &str, network: Option<&str>) {
+    let network = network_arg(network);
+    let mut book = load_book();
+
+    let (label, replaced) = book
+        .add(label, address, network)
+        .unwrap_or_else(|e| exit_with(e));
+    save_book(&book);
+
+    match replaced {
+        Some(old) => println!(
+            "Updated {} ({}): {} -> {}",
+            label,
+            scope(network),
+            old,
+            address
+        ),
+        None => println!("Added {} ({}): {}", label, scope(network), address),
+    }
+}
+
+pub fn remove(label: &str, network: Option<&str>) {
+    let network = network_arg(network);
+    let label = normalize_label(label).unwrap_or_else(|e| exit_with(e));
+    let mut book = load_book();
+
+    match book.remove(&label, network) {
+        Some(address) => {
+            save_book(&book);
+            println!("Removed {} ({}): {}", label, scope(network), address);
+        }
+        None => exit_with(format!("No label '{}' in {}.", label, scope(network))),
+    }
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    label: &'a str,
+    /// Network name, or "global"
+    network: &'a str,
+    address: &'a str,
+}
+
+#[derive(Serialize)]
+struct EntryList<'a> {
+    addresses: Vec<Entry<'a>>,
+}
+
+pub fn list(network: Option<&str>, format: OutputFormat) {
+    let network = network_arg(network);
+    let book = load_book();
+
+    let global = book
+        .global
+        .iter()
+        .map(|(label, address)| ("global", label, address));
+    let per_network = book
+        .networks
+        .iter()
+        .filter(|(name, _)| network.is_none_or(|n| n.name == name.as_str()))
+        .flat_map(|(name, labels)| labels.iter().map(move |(l, a)| (name.as_str(), l, a)));
+    let entries: Vec<Entry> = global
+        .chain(per_network)
+        .map(|(network, label, address)| Entry {
+            label,
+            network,
+            address,
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Json => return print_serialized(&entries, format),
+        OutputFormat::Toml => {
+            return print_serialized(&EntryList { addresses: entries }, format);
+        }
+        OutputFormat::Plain => {
+            for entry in entries {
+                println!("{}\t{}\t{}", entry.label, entry.network, entry.address);
+            }
+            return;
+        }
+        OutputFormat::Table => {}
+    }
+
+    if entries.is_empty() {
+        println!("No addresses saved. Add one with 'stargate addr add <label> <address>'.");
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|e| {
+            vec![
+                e.label.to_string(),
+                e.network.to_string(),
+                e.address.to_string(),
+            ]
+        })
+        .collect();
+    for line in render_table(&["LABEL", "NETWORK", "ADDRESS"], &rows) {
+        println!("{}", line);
+    }
+}
+
+/// Prints the labels usable on a network (default: the active one) as `ADDR_<LABEL>` variables.
+pub fn export(network: Option<&str>, format: EnvFormat) {
+    let network = network_or_current(network);
+    let book = load_book();
+
+    let vars = book
+        .labels_for(network)
+        .into_iter()
+        .map(|(label, address)| (env_var(label), Some(address.to_string())))
+        .collect();
+    match render_vars(vars, format) {
+        Ok(output) if output.is_empty() => {}
+        Ok(output) => println!("{}", output),
+        Err(e) => exit_with(e),
+    }
+}
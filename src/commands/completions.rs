@@ -0,0 +1,129 @@
+use crate::networks::all_networks;
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, Command};
+use clap_complete::{Generator, Shell};
+use clap_complete_nushell::Nushell;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Nu,
+    Pwsh,
+}
+
+/// Subcommand paths and the argument that takes a network name.
+const NETWORK_ARGS: &[(&[&str], &str)] = &[
+    (&["switch"], "network"),
+    (&["info"], "network"),
+    (&["env"], "network"),
+    (&["config", "set", "default-network"], "network"),
+    (&["exec"], "network"),
+    (&["fork"], "network"),
+    (&["each"], "networks"),
+    (&["ping"], "networks"),
+    (&["addr", "add"], "network"),
+    (&["addr", "list"], "network"),
+    (&["addr", "remove"], "network"),
+    (&["addr", "export"], "network"),
+];
+
+/// Every name and alias in the merged registry (built-ins plus imported networks).
+fn network_names() -> Vec<&'static str> {
+    all_networks().flat_map(|n| n.names()).collect()
+}
+
+/// `config set` takes any key, so completions get a `default-network` subcommand
+/// whose value can be completed like the other network arguments.
+fn with_default_network_key(cmd: Command) -> Command {
+    cmd.mut_subcommand("config", |config| {
+        config.mut_subcommand("set", |set| {
+            set.subcommand(
+                Command::new("default-network")
+                    .about("Default network for new shells")
+                    .arg(Arg::new("network").required(true)),
+            )
+        })
+    })
+}
+
+/// Attaches the known network names to every network argument. Only used for
+/// generating completions: parsing must keep accepting chain IDs and `-`.
+fn with_network_values(cmd: Command, names: &[&'static str]) -> Command {
+    let mut cmd = with_default_network_key(cmd);
+    for (path, arg) in NETWORK_ARGS {
+        cmd = mut_arg_at(cmd, path, arg, names);
+    }
+    cmd
+}
+
+fn mut_arg_at(cmd: Command, path: &[&str], arg: &str, names: &[&'static str]) -> Command {
+    match path {
+        [] => cmd.mut_arg(arg, |a| {
+            a.value_parser(PossibleValuesParser::new(names.iter().copied()))
+        }),
+        [first, rest @ ..] => cmd.mut_subcommand(*first, |sub| mut_arg_at(sub, rest, arg, names)),
+    }
+}
+
+fn generate(generator: impl Generator, cmd: &mut Command, bin_name: &str) {
+    clap_complete::generate(generator, cmd, bin_name, &mut io::stdout());
+}
+
+pub fn run(cmd: Command, shell: CompletionShell, bin_name: &str) {
+    let mut cmd = with_network_values(cmd, &network_names());
+
+    match shell {
+        CompletionShell::Bash => generate(Shell::Bash, &mut cmd, bin_name),
+        CompletionShell::Zsh => generate(Shell::Zsh, &mut cmd, bin_name),
+        CompletionShell::Fish => generate(Shell::Fish, &mut cmd, bin_name),
+        CompletionShell::Pwsh => generate(Shell::PowerShell, &mut cmd, bin_name),
+        CompletionShell::Nu => generate(Nushell, &mut cmd, bin_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::CommandFactory;
+
+    fn possible_values(cmd: &Command, path: &[&str], arg: &str) -> Vec<String> {
+        let mut cmd = cmd;
+        for name in path {
+            cmd = cmd.find_subcommand(name).unwrap();
+        }
+        cmd.get_arguments()
+            .find(|a| a.get_id() == arg)
+            .unwrap()
+            .get_possible_values()
+            .iter()
+            .map(|v| v.get_name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn network_names_include_aliases() {
+        let names = network_names();
+        assert!(names.contains(&"mainnet"));
+        assert!(names.contains(&"eth"));
+        assert!(names.contains(&"arb"));
+    }
+
+    #[test]
+    fn attaches_network_names_to_every_network_argument() {
+        let cmd = with_network_values(Cli::command(), &["mainnet", "eth"]);
+
+        for (path, arg) in NETWORK_ARGS {
+            assert_eq!(possible_values(&cmd, path, arg), vec!["mainnet", "eth"]);
+        }
+    }
+
+    #[test]
+    fn network_values_keep_the_command_valid() {
+        // mut_arg re-appends arguments, which must not reorder positionals
+        with_network_values(Cli::command(), &["mainnet"]).debug_assert();
+    }
+}
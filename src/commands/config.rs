@@ -1,6 +1,27 @@
-use crate::config::Config;
+use crate::commands::{exit_with, load_config};
+use crate::config::{Config, ConfigError, is_secret_key, replace_config_file, write_private};
+use crate::networks::{find_network, is_address};
+use crate::output::{OutputFormat, print_serialized};
+use crate::paths;
+use crate::redact::MASK;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::Command;
+
+fn set_api_key(key: Option<String>, encrypt: bool) {
+    let mut config = load_config();
+    if encrypt && let Some(profile) = &config.profile {
+        exit_with(format!(
+            "Profile '{}' is selected, but the vault holds a single key shared by all profiles. \
+             Give the profile an api_key_env or api_key_command instead.",
+            profile
+        ));
+    }
 
-pub fn set_api_key(key: Option<String>) {
     let key = match key {
         Some(k) => k,
         None => prompt_api_key(),
@@ -11,14 +32,32 @@ pub fn set_api_key(key: Option<String>) {
         std::process::exit(1);
     }
 
-    let mut config = Config::load();
+    if encrypt {
+        crate::commands::vault::set_api_key(key);
+        return;
+    }
 
-    if let Err(e) = config.set_api_key(key) {
+    config.set_api_key(key);
+    if let Err(e) = config.save() {
         eprintln!("Failed to save config: {}", e);
         std::process::exit(1);
     }
 
-    println!("API key saved successfully.");
+    match &config.profile {
+        Some(profile) => println!("API key saved to profile '{}'.", profile),
+        None => println!("API key saved successfully."),
+    }
+    note_override(&config, "api_key");
+}
+
+/// Points out when a value just written is shadowed by a `STARGATE_*` variable.
+fn note_override(config: &Config, key: &str) {
+    if let Some(o) = config.env_override(key) {
+        println!(
+            "Note: ${} is set and takes precedence over config.toml in this environment.",
+            o.var
+        );
+    }
 }
 
 fn prompt_api_key() -> String {
@@ -32,15 +71,25 @@ fn prompt_api_key() -> String {
     }
 }
 
-pub fn set_default_network(network: String) {
-    let mut config = Config::load();
+fn set_default_network(network: String) {
+    let mut config = load_config();
 
-    match config.set_default_network(network.clone()) {
+    match config
+        .set_default_network(network.clone())
+        .and_then(|()| config.save())
+    {
         Ok(()) => {
             // Show canonical name that was stored (may differ from input if alias was used)
-            let stored = config.default_network.as_ref().unwrap();
-            println!("Default network set to '{}' successfully.", stored);
+            let stored = find_network(&network).map_or(network.as_str(), |n| n.name);
+            match &config.profile {
+                Some(profile) => println!(
+                    "Default network for profile '{}' set to '{}' successfully.",
+                    profile, stored
+                ),
+                None => println!("Default network set to '{}' successfully.", stored),
+            }
             println!("This will be used when you start a new shell.");
+            note_override(&config, "default_network");
         }
         Err(e) => {
             eprintln!("{}", e);
@@ -49,13 +98,483 @@ pub fn set_default_network(network: String) {
     }
 }
 
-pub fn get_default_network() {
-    let config = Config::load();
+#[derive(Serialize)]
+struct DefaultNetwork<'a> {
+    default_network: &'a str,
+    /// False when falling back to the built-in default
+    configured: bool,
+}
+
+fn get_default_network(format: OutputFormat) {
+    let config = load_config();
     let default = config.get_default_network();
 
-    if config.default_network.is_some() {
+    let view = DefaultNetwork {
+        default_network: default,
+        configured: config.configured_default_network().is_some(),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Toml => return print_serialized(&view, format),
+        OutputFormat::Plain => return println!("{}", default),
+        OutputFormat::Table => {}
+    }
+
+    if let Some(o) = config.env_override("default_network") {
+        println!("Default network: {} (from ${})", default, o.var);
+    } else if let Some(profile) = &config.profile
+        && config
+            .selected_profile()
+            .is_some_and(|p| p.default_network.is_some())
+    {
+        println!("Default network: {} (profile '{}')", default, profile);
+    } else if config.configured_default_network().is_some() {
         println!("Default network: {}", default);
     } else {
         println!("Default network: {} (system default)", default);
     }
 }
+
+/// Parses a command-line value as TOML (`true`, `42`, `["a", "b"]`), falling back
+/// to a plain string so `config set networks.base.color blue` needs no quotes.
+/// Addresses stay strings rather than becoming hex integers.
+fn parse_value(raw: &str) -> toml::Value {
+    if is_address(raw) {
+        return toml::Value::String(raw.to_string());
+    }
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn masked(key: &str, value: toml::Value, reveal: bool) -> toml::Value {
+    if is_secret_key(key) && !reveal {
+        toml::Value::String(MASK.to_string())
+    } else {
+        value
+    }
+}
+
+/// Strings print bare; everything else in TOML syntax.
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// `config set <key> [value]`; `api-key` and `default-network` keep their prompts and validation.
+pub fn set(key: &str, value: Option<String>, encrypt: bool) {
+    match key {
+        "api-key" | "api_key" => return set_api_key(value, encrypt),
+        _ if encrypt => exit_with("--encrypt only applies to api-key."),
+        _ => {}
+    }
+
+    let Some(value) = value else {
+        exit_with(format!("Missing value for '{}'.", key));
+    };
+
+    if matches!(key, "default-network" | "default_network") {
+        return set_default_network(value);
+    }
+
+    let mut config = load_config();
+    let value = parse_value(&value);
+    if let Err(e) = config.set_key(key, value.clone()) {
+        exit_with(e);
+    }
+    if let Err(e) = config.save() {
+        exit_with(format!("Failed to save config: {}", e));
+    }
+
+    println!(
+        "Set {} = {}",
+        key,
+        display_value(&masked(key, value, false))
+    );
+    note_override(&config, key);
+}
+
+pub fn get(key: &str, reveal: bool, format: OutputFormat) {
+    if matches!(key, "default-network" | "default_network") {
+        return get_default_network(format);
+    }
+
+    let config = load_config();
+    let value = match config.env_override(key) {
+        Some(o) => Ok(Some(toml::Value::String(o.value.clone()))),
+        None => config.get_key(key),
+    };
+    let value = match value {
+        Ok(Some(value)) => masked(key, value, reveal),
+        Ok(None) => exit_with(format!("'{}' is not set.", key)),
+        Err(e) => exit_with(e),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Toml => {
+            print_serialized(&BTreeMap::from([(key, value)]), format)
+        }
+        OutputFormat::Table | OutputFormat::Plain => println!("{}", display_value(&value)),
+    }
+}
+
+pub fn unset(key: &str) {
+    let mut config = load_config();
+    if let Err(e) = config.unset_key(key) {
+        exit_with(e);
+    }
+    if let Err(e) = config.save() {
+        exit_with(format!("Failed to save config: {}", e));
+    }
+    println!("Unset {}.", key);
+    note_override(&config, key);
+}
+
+/// Every effective value with the `STARGATE_*` variable it came from, if any;
+/// overrides replace the file's value for the same key, and key setting overrides
+/// mark the other key settings as shadowed.
+fn effective_entries(config: &Config) -> Result<Vec<Entry>, String> {
+    let mut entries: BTreeMap<String, Entry> = config
+        .entries()?
+        .into_iter()
+        .map(|(key, value)| {
+            let entry = Entry {
+                shadowed_by: config.shadowed_by(&key),
+                key: key.clone(),
+                value,
+                origin: Origin::Config,
+                var: None,
+            };
+            (key, entry)
+        })
+        .collect();
+
+    for o in &config.overrides {
+        entries.insert(
+            o.key.to_string(),
+            Entry {
+                key: o.key.to_string(),
+                value: toml::Value::String(o.value.clone()),
+                origin: Origin::Env,
+                var: Some(o.var),
+                shadowed_by: None,
+            },
+        );
+    }
+    Ok(entries.into_values().collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Origin {
+    /// config.toml
+    Config,
+    /// A `STARGATE_*` variable
+    Env,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    #[serde(skip)]
+    key: String,
+    value: toml::Value,
+    origin: Origin,
+    /// Environment variable the value came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    var: Option<&'static str>,
+    /// Environment variable that makes stargate ignore this config.toml value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shadowed_by: Option<&'static str>,
+}
+
+pub fn list(reveal: bool, format: OutputFormat) {
+    let config = load_config();
+    let entries: Vec<Entry> = match effective_entries(&config) {
+        Ok(entries) => entries
+            .into_iter()
+            .map(|entry| Entry {
+                value: masked(&entry.key, entry.value, reveal),
+                ..entry
+            })
+            .collect(),
+        Err(e) => exit_with(e),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Toml => print_serialized(
+            &entries
+                .into_iter()
+                .map(|entry| (entry.key.clone(), entry))
+                .collect::<BTreeMap<_, _>>(),
+            format,
+        ),
+        OutputFormat::Table | OutputFormat::Plain => {
+            if entries.is_empty() {
+                println!("No configuration set.");
+            }
+            for entry in entries {
+                match (entry.var, entry.shadowed_by) {
+                    (Some(var), _) => println!("{} = {}  (from ${})", entry.key, entry.value, var),
+                    (None, Some(var)) => {
+                        println!("{} = {}  (shadowed by ${})", entry.key, entry.value, var)
+                    }
+                    (None, None) => println!("{} = {}", entry.key, entry.value),
+                }
+            }
+        }
+    }
+}
+
+pub fn use_profile(name: &str) {
+    let mut config = load_config();
+    if !config.profiles.contains_key(name) {
+        exit_with(ConfigError::UnknownProfile {
+            name: name.to_string(),
+            known: config.profiles.keys().cloned().collect(),
+        });
+    }
+
+    config.active_profile = Some(name.to_string());
+    if let Err(e) = config.save() {
+        exit_with(format!("Failed to save config: {}", e));
+    }
+    println!("Profile '{}' is now the default for new shells.", name);
+
+    if let Ok(current) = env::var("STARGATE_PROFILE")
+        && !current.is_empty()
+        && current != name
+    {
+        println!(
+            "This shell still uses STARGATE_PROFILE={}; unset it or open a new shell to use '{}'.",
+            current, name
+        );
+    }
+}
+
+pub fn path(state: bool, cache: bool) {
+    let path = if state {
+        paths::state_dir()
+    } else if cache {
+        paths::cache_dir()
+    } else {
+        Config::path()
+    };
+    match path {
+        Some(path) => println!("{}", path.display()),
+        None => exit_with("Could not determine home directory"),
+    }
+}
+
+fn run_editor(file: &Path) -> Result<(), String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Through the shell, so editors with arguments (`code --wait`) work
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(file)
+        .status()
+        .map_err(|e| format!("Failed to start editor '{}': {}", editor, e))?;
+
+    if !status.success() {
+        return Err(format!("Editor '{}' exited with {}", editor, status));
+    }
+    Ok(())
+}
+
+fn ask_reopen() -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    eprint!("Re-open the editor? [Y/n] ");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && !matches!(answer.trim().to_lowercase().as_str(), "n" | "no")
+}
+
+/// Edits a draft copy of `config.toml` and only replaces the real file once the
+/// draft parses.
+fn edit_config(path: &Path) -> Result<bool, String> {
+    let original = match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let draft = path.with_extension("toml.edit");
+    write_private(&draft, original.as_deref().unwrap_or(""))
+        .map_err(|e| format!("Failed to create {}: {}", draft.display(), e))?;
+
+    let content = loop {
+        if let Err(e) = run_editor(&draft) {
+            let _ = fs::remove_file(&draft);
+            return Err(e);
+        }
+        let content = fs::read_to_string(&draft)
+            .map_err(|e| format!("Failed to read {}: {}", draft.display(), e))?;
+
+        match Config::parse(path, &content) {
+            Ok(_) => break content,
+            Err(e) => {
+                eprintln!("{}", e);
+                if !ask_reopen() {
+                    let _ = fs::remove_file(&draft);
+                    return Err("Changes discarded.".to_string());
+                }
+            }
+        }
+    };
+
+    let _ = fs::remove_file(&draft);
+    if original.as_deref() == Some(content.as_str()) {
+        return Ok(false);
+    }
+
+    replace_config_file(path, original.as_deref(), &content)?;
+    Ok(true)
+}
+
+pub fn edit() {
+    let Some(path) = Config::path() else {
+        exit_with("Could not determine home directory");
+    };
+
+    match edit_config(&path) {
+        Ok(true) => println!("Saved {}.", path.display()),
+        Ok(false) => println!("No changes."),
+        Err(e) => exit_with(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EnvOverride;
+
+    #[test]
+    fn parse_value_reads_toml_and_falls_back_to_strings() {
+        assert_eq!(parse_value("true"), toml::Value::Boolean(true));
+        assert_eq!(parse_value("42"), toml::Value::Integer(42));
+        assert_eq!(
+            parse_value(r#"["l2", "cheap"]"#),
+            toml::Value::Array(vec!["l2".into(), "cheap".into()])
+        );
+        assert_eq!(parse_value("blue"), toml::Value::String("blue".to_string()));
+        let address = "0x00000000000000000000000000000000000000aa";
+        assert_eq!(
+            parse_value(address),
+            toml::Value::String(address.to_string())
+        );
+        assert_eq!(
+            parse_value("pass show alchemy"),
+            toml::Value::String("pass show alchemy".to_string())
+        );
+    }
+
+    #[test]
+    fn secrets_are_masked_unless_revealed() {
+        let key = toml::Value::String("secret".to_string());
+        assert_eq!(display_value(&masked("api_key", key.clone(), false)), MASK);
+        assert_eq!(
+            display_value(&masked("api_key", key.clone(), true)),
+            "secret"
+        );
+        assert_eq!(
+            display_value(&masked("default_network", key, false)),
+            "secret"
+        );
+    }
+
+    #[test]
+    fn overrides_replace_file_values_in_listing() {
+        let config = Config {
+            default_network: Some("sepolia".to_string()),
+            groups: [("l2".to_string(), vec!["base".to_string()])].into(),
+            overrides: vec![EnvOverride {
+                var: "STARGATE_DEFAULT_NETWORK",
+                key: "default_network",
+                value: "base".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let entries = effective_entries(&config).unwrap();
+        let listed: Vec<(&str, &toml::Value, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), &e.value, e.var))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (
+                    "default_network",
+                    &toml::Value::String("base".to_string()),
+                    Some("STARGATE_DEFAULT_NETWORK")
+                ),
+                ("groups.l2", &toml::Value::Array(vec!["base".into()]), None),
+                ("version", &toml::Value::Integer(1), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_overrides_shadow_other_key_settings() {
+        let config = Config {
+            api_key_command: Some("pass show alchemy".to_string()),
+            default_network: Some("base".to_string()),
+            profiles: [(
+                "prod".to_string(),
+                crate::config::Profile {
+                    api_key_env: Some("PROD_KEY".to_string()),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            overrides: vec![EnvOverride {
+                var: "STARGATE_ALCHEMY_API_KEY",
+                key: "api_key",
+                value: "container".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let entries = effective_entries(&config).unwrap();
+        let shadowed: Vec<(&str, Origin, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.origin, e.shadowed_by))
+            .collect();
+        assert_eq!(
+            shadowed,
+            vec![
+                ("api_key", Origin::Env, None),
+                (
+                    "api_key_command",
+                    Origin::Config,
+                    Some("STARGATE_ALCHEMY_API_KEY")
+                ),
+                ("default_network", Origin::Config, None),
+                (
+                    "profiles.prod.api_key_env",
+                    Origin::Config,
+                    Some("STARGATE_ALCHEMY_API_KEY")
+                ),
+                ("version", Origin::Config, None),
+            ]
+        );
+
+        let json = serde_json::to_value(&entries[0]).unwrap();
+        assert_eq!(json["origin"], "env");
+        assert_eq!(json["var"], "STARGATE_ALCHEMY_API_KEY");
+    }
+}
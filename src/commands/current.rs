@@ -1,15 +1,38 @@
+use crate::commands::switch::NetworkExports;
+use crate::output::{OutputFormat, print_serialized};
+use crate::redact::redact_url;
 use std::env;
 
-pub fn run() {
-    let network = env::var("STARGATE_NETWORK").ok();
-    let chain_id = env::var("STARGATE_CHAIN_ID").ok();
+pub fn run(format: OutputFormat, reveal: bool) {
+    let mut exports = NetworkExports::from_env();
 
-    match (network, chain_id) {
-        (Some(net), Some(id)) => {
-            println!("{} ({})", net, id);
+    if !reveal && let Some(exports) = exports.as_mut() {
+        exports.rpc_url = redact_url(&exports.rpc_url);
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Toml => {
+            if let Some(exports) = exports {
+                print_serialized(&exports, format);
+            } else if format == OutputFormat::Json {
+                println!("null");
+            }
         }
-        _ => {
-            println!("No network selected. Run 'switch <network>' first.");
+        OutputFormat::Plain => {
+            if let Some(exports) = exports {
+                println!("{}\t{}", exports.network_name, exports.chain_id);
+            }
         }
+        OutputFormat::Table => match exports {
+            Some(exports) => {
+                println!("{} ({})", exports.network_name, exports.chain_id);
+            }
+            None if env::var_os("ETH_RPC_URL").is_some() => {
+                println!("No network selected (ETH_RPC_URL is set outside stargate).");
+            }
+            None => {
+                println!("No network selected. Run 'switch <network>' first.");
+            }
+        },
     }
 }
@@ -1,10 +1,14 @@
+use crate::redact::redact_url;
 use std::env;
 
-pub fn run() {
+pub fn run(reveal: bool) {
     match env::var("ETH_RPC_URL").ok() {
-        Some(rpc_url) => {
+        Some(rpc_url) if reveal => {
             println!("{}", rpc_url);
         }
+        Some(rpc_url) => {
+            println!("{}", redact_url(&rpc_url));
+        }
         None => {
             println!("No network selected. Run 'switch <network>' first.");
         }
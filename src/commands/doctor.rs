@@ -0,0 +1,409 @@
+use crate::config::{
+    Config, KEYS, NETWORK_KEYS, PROFILE_KEYS, invalid_env_overrides, is_readable_by_others, migrate,
+};
+use crate::networks::{edit_distance, find_network, is_address};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// A repair `config doctor` can apply.
+#[derive(Debug, PartialEq)]
+enum Fix {
+    /// Move an unparseable file aside so stargate starts from defaults
+    MoveAside,
+    RestrictPermissions,
+    RemoveDefaultNetwork,
+    RemoveProfileDefaultNetwork(String),
+    RemoveActiveProfile,
+    RemoveNetworkSettings(String),
+    RemoveGroupMember {
+        group: String,
+        member: String,
+    },
+}
+
+impl Fix {
+    fn describe(&self) -> String {
+        match self {
+            Fix::MoveAside => "move the file to config.toml.broken".to_string(),
+            Fix::RestrictPermissions => "chmod 600".to_string(),
+            Fix::RemoveDefaultNetwork => "unset default_network".to_string(),
+            Fix::RemoveProfileDefaultNetwork(profile) => {
+                format!("unset default_network in [profiles.{}]", profile)
+            }
+            Fix::RemoveActiveProfile => "unset active_profile".to_string(),
+            Fix::RemoveNetworkSettings(key) => format!("remove [networks.{}]", key),
+            Fix::RemoveGroupMember { group, member } => {
+                format!("remove '{}' from group '{}'", member, group)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Problem {
+    message: String,
+    fix: Option<Fix>,
+}
+
+impl Problem {
+    fn new(message: String, fix: Option<Fix>) -> Self {
+        Self { message, fix }
+    }
+}
+
+/// `apikey` → "did you mean 'api_key'?"
+fn unknown_key_message(key: &str, section: &str, known: &[&str]) -> String {
+    let suggestion = known
+        .iter()
+        .filter(|k| edit_distance(key, k) <= 2)
+        .min_by_key(|k| edit_distance(key, k));
+    match suggestion {
+        Some(s) => format!("Unknown key '{}'{}. Did you mean '{}'?", key, section, s),
+        None => format!("Unknown key '{}'{} is ignored.", key, section),
+    }
+}
+
+/// Problems in a config that parsed: typos in keys and references to unknown networks.
+fn diagnose(config: &Config, table: &toml::Table) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    for key in table.keys().filter(|k| !KEYS.contains(&k.as_str())) {
+        problems.push(Problem::new(unknown_key_message(key, "", KEYS), None));
+    }
+
+    for (parent, known) in [("networks", NETWORK_KEYS), ("profiles", PROFILE_KEYS)] {
+        let Some(toml::Value::Table(sections)) = table.get(parent) else {
+            continue;
+        };
+        for (name, settings) in sections {
+            let Some(settings) = settings.as_table() else {
+                continue;
+            };
+            let section = format!(" in [{}.{}]", parent, name);
+            for key in settings.keys().filter(|k| !known.contains(&k.as_str())) {
+                problems.push(Problem::new(
+                    unknown_key_message(key, &section, known),
+                    None,
+                ));
+            }
+        }
+    }
+
+    if let Some(default) = &config.default_network
+        && find_network(default).is_none()
+    {
+        problems.push(Problem::new(
+            format!("default_network '{}' is not a known network.", default),
+            Some(Fix::RemoveDefaultNetwork),
+        ));
+    }
+
+    for (name, profile) in &config.profiles {
+        if let Some(default) = &profile.default_network
+            && find_network(default).is_none()
+        {
+            problems.push(Problem::new(
+                format!(
+                    "default_network '{}' in [profiles.{}] is not a known network.",
+                    default, name
+                ),
+                Some(Fix::RemoveProfileDefaultNetwork(name.clone())),
+            ));
+        }
+    }
+
+    if let Some(active) = &config.active_profile
+        && !config.profiles.contains_key(active)
+    {
+        problems.push(Problem::new(
+            format!(
+                "active_profile '{}' does not match any [profiles] section.",
+                active
+            ),
+            Some(Fix::RemoveActiveProfile),
+        ));
+    }
+
+    for key in config.networks.keys() {
+        if find_network(key).is_none() {
+            problems.push(Problem::new(
+                format!("[networks.{}] does not match any network.", key),
+                Some(Fix::RemoveNetworkSettings(key.clone())),
+            ));
+        }
+    }
+
+    for (key, settings) in &config.networks {
+        if let Some(from) = &settings.from
+            && !is_address(from)
+        {
+            problems.push(Problem::new(
+                format!("from '{}' in [networks.{}] is not an address.", from, key),
+                None,
+            ));
+        }
+    }
+
+    for (group, members) in &config.groups {
+        for member in members {
+            if find_network(member).is_none() {
+                problems.push(Problem::new(
+                    format!("Group '{}' references unknown network '{}'.", group, member),
+                    Some(Fix::RemoveGroupMember {
+                        group: group.clone(),
+                        member: member.clone(),
+                    }),
+                ));
+            }
+        }
+    }
+
+    if let Some(var) = &config.api_key_env
+        && env::var(var).is_err()
+    {
+        problems.push(Problem::new(
+            format!("api_key_env is '{}', but ${} is not set.", var, var),
+            None,
+        ));
+    }
+
+    problems
+}
+
+fn apply(path: &Path, config: Option<Config>, fixes: &[&Fix]) -> Result<(), String> {
+    if fixes.contains(&&Fix::MoveAside) {
+        let broken = path.with_extension("toml.broken");
+        fs::rename(path, &broken).map_err(|e| format!("Failed to move config: {}", e))?;
+        println!("Moved {} to {}.", path.display(), broken.display());
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    if fixes.contains(&&Fix::RestrictPermissions) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to change permissions: {}", e))?;
+    }
+
+    let Some(mut config) = config else {
+        return Ok(());
+    };
+    let mut changed = false;
+    for fix in fixes {
+        match fix {
+            Fix::RemoveDefaultNetwork => config.default_network = None,
+            Fix::RemoveProfileDefaultNetwork(name) => {
+                if let Some(profile) = config.profiles.get_mut(name) {
+                    profile.default_network = None;
+                }
+            }
+            Fix::RemoveActiveProfile => config.active_profile = None,
+            Fix::RemoveNetworkSettings(key) => {
+                config.networks.remove(key);
+            }
+            Fix::RemoveGroupMember { group, member } => {
+                if let Some(members) = config.groups.get_mut(group) {
+                    members.retain(|m| m != member);
+                }
+            }
+            Fix::MoveAside | Fix::RestrictPermissions => continue,
+        }
+        changed = true;
+    }
+
+    if changed {
+        config.save()?;
+    }
+    Ok(())
+}
+
+fn confirm(count: usize) -> bool {
+    eprint!("Apply {} fix(es)? [y/N] ", count);
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+pub fn run(fix: bool) {
+    let Some(path) = Config::path() else {
+        eprintln!("Could not determine home directory");
+        std::process::exit(1);
+    };
+
+    // Bad `STARGATE_*` overrides stop every command, with or without a file
+    let mut problems: Vec<Problem> = invalid_env_overrides(|name| env::var(name).ok())
+        .into_iter()
+        .map(|e| Problem::new(format!("{}. Unset it or fix its value.", e), None))
+        .collect();
+
+    if !path.exists() {
+        if problems.is_empty() {
+            println!("No config file at {}; using defaults.", path.display());
+            return;
+        }
+        for problem in &problems {
+            println!("- {}", problem.message);
+        }
+        std::process::exit(1);
+    }
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    if is_readable_by_others(&path) {
+        problems.push(Problem::new(
+            format!("{} is readable by other users.", path.display()),
+            Some(Fix::RestrictPermissions),
+        ));
+    }
+
+    // A newer layout can't be checked (or repaired) by this release; an older one
+    // is checked as the next save will write it
+    let content = match migrate(&content) {
+        Ok(Some((_, migrated))) => Some(migrated),
+        Ok(None) => Some(content),
+        Err(message) => {
+            problems.push(Problem::new(message, None));
+            None
+        }
+    };
+
+    let config = content.and_then(|content| match Config::parse(&path, &content) {
+        Ok(config) => {
+            let table: toml::Table = content.parse().unwrap_or_default();
+            problems.extend(diagnose(&config, &table));
+            Some(config)
+        }
+        Err(e) => {
+            problems.push(Problem::new(e.to_string(), Some(Fix::MoveAside)));
+            None
+        }
+    });
+
+    if problems.is_empty() {
+        println!("No problems found in {}.", path.display());
+        return;
+    }
+
+    for problem in &problems {
+        println!("- {}", problem.message);
+        if let Some(fix) = &problem.fix {
+            println!("  fix: {}", fix.describe());
+        }
+    }
+
+    let fixes: Vec<&Fix> = problems.iter().filter_map(|p| p.fix.as_ref()).collect();
+    if fixes.is_empty() {
+        std::process::exit(1);
+    }
+
+    if !fix {
+        if !io::stdin().is_terminal() {
+            println!("Run 'stargate config doctor --fix' to apply the fixes.");
+            std::process::exit(1);
+        }
+        if !confirm(fixes.len()) {
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = apply(&path, config, &fixes) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    println!("Applied {} fix(es).", fixes.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnose_str(content: &str) -> Vec<Problem> {
+        let config: Config = toml::from_str(content).unwrap();
+        diagnose(&config, &content.parse().unwrap())
+    }
+
+    #[test]
+    fn healthy_config_has_no_problems() {
+        let problems = diagnose_str(
+            r#"
+default_network = "base"
+
+[networks.mainnet]
+tags = ["prod"]
+
+[groups]
+l2 = ["base", "op"]
+"#,
+        );
+        assert!(problems.is_empty(), "{:?}", problems);
+    }
+
+    #[test]
+    fn typos_in_keys_get_suggestions() {
+        let problems = diagnose_str("apikey = \"k\"\n\n[networks.base]\ncolour = \"red\"\n");
+        assert_eq!(problems.len(), 2);
+        assert_eq!(
+            problems[0].message,
+            "Unknown key 'apikey'. Did you mean 'api_key'?"
+        );
+        assert_eq!(
+            problems[1].message,
+            "Unknown key 'colour' in [networks.base]. Did you mean 'color'?"
+        );
+        assert!(problems.iter().all(|p| p.fix.is_none()));
+    }
+
+    #[test]
+    fn unknown_networks_are_fixable() {
+        let problems = diagnose_str(
+            "default_network = \"mainnt\"\n\n[networks.nope]\ntags = []\n\n[groups]\ng = [\"base\", \"nope\"]\n",
+        );
+        let fixes: Vec<&Fix> = problems.iter().filter_map(|p| p.fix.as_ref()).collect();
+        assert_eq!(
+            fixes,
+            vec![
+                &Fix::RemoveDefaultNetwork,
+                &Fix::RemoveNetworkSettings("nope".to_string()),
+                &Fix::RemoveGroupMember {
+                    group: "g".to_string(),
+                    member: "nope".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_sender_is_reported() {
+        let problems = diagnose_str("[networks.base]\nfrom = \"deployer\"\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].message,
+            "from 'deployer' in [networks.base] is not an address."
+        );
+    }
+
+    #[test]
+    fn profile_problems_are_reported() {
+        let problems = diagnose_str(
+            "active_profile = \"prod\"\n\n[profiles.staging]\ndefault_network = \"nope\"\napikey = \"k\"\n",
+        );
+        let messages: Vec<&str> = problems.iter().map(|p| p.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown key 'apikey' in [profiles.staging]. Did you mean 'api_key'?",
+                "default_network 'nope' in [profiles.staging] is not a known network.",
+                "active_profile 'prod' does not match any [profiles] section.",
+            ]
+        );
+        assert_eq!(problems[2].fix, Some(Fix::RemoveActiveProfile));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::commands::switch::{NetworkExports, confirm, print_protected_banner};
+use crate::commands::{NetworkSet, load_config};
+use crate::config::Config;
+use crate::networks::Network;
+use std::process::{Command, ExitStatus};
+
+/// Runs `command` with the environment `switch` would set for `network`.
+pub fn run_with_network(
+    config: &Config,
+    network: &Network,
+    command: &[String],
+) -> Result<ExitStatus, String> {
+    let api_key = config.api_key_for(network)?;
+    let exports = NetworkExports::from_network(network, &api_key)
+        .with_settings(network, config)
+        .with_shell_state(|name| std::env::var(name).ok());
+
+    let (program, args) = command.split_first().ok_or("No command given.")?;
+    let mut child = Command::new(program);
+    child.args(args);
+    for (name, value) in exports.vars() {
+        match value {
+            Some(value) => child.env(name, value),
+            None => child.env_remove(name),
+        };
+    }
+
+    child
+        .status()
+        .map_err(|e| format!("Failed to run '{}': {}", program, e))
+}
+
+/// Shows the banner for each protected network in `networks` and, unless `yes`, asks
+/// before running anything. Exits if the user declines or cannot be asked.
+pub fn confirm_protected(config: &Config, networks: &[&Network], yes: bool) {
+    for network in networks.iter().filter(|n| config.is_protected(n)) {
+        print_protected_banner(network);
+        if yes {
+            continue;
+        }
+        match confirm(network, "run on") {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!("Aborted.");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Runs `command` once per selected network, continuing past failures unless
+/// `fail_fast` is set. Exits non-zero if any run failed.
+pub fn run(set: &NetworkSet, fail_fast: bool, yes: bool, command: &[String]) {
+    let config = load_config();
+    let networks = match set.resolve(&config) {
+        Ok(Some(networks)) => networks,
+        Ok(None) => {
+            eprintln!("Select networks with --network, --tag or --group.");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    confirm_protected(&config, &networks, yes);
+
+    let mut failed = Vec::new();
+    for network in networks {
+        eprintln!("==> {} ({})", network.name, network.chain_id);
+        match run_with_network(&config, network, command) {
+            Ok(status) if status.success() => continue,
+            Ok(status) => eprintln!("{} failed: {}", network.name, status),
+            Err(e) => eprintln!("{}: {}", network.name, e),
+        }
+        failed.push(network.name);
+        if fail_fast {
+            break;
+        }
+    }
+
+    if !failed.is_empty() {
+        eprintln!("Failed on: {}", failed.join(", "));
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::commands::load_config;
+use crate::commands::switch::{NetworkExports, shell_export};
+use crate::networks::{find_network, unknown_network_message};
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum EnvFormat {
+    /// `export KEY="value"` statements, for eval
+    Shell,
+    /// `.env` file for dotenv
+    Dotenv,
+    /// `.env` file for docker compose
+    Compose,
+    /// `$GITHUB_ENV` / `$GITHUB_OUTPUT` file syntax
+    Github,
+    /// `docker run --env-file`
+    Docker,
+    /// JSON object
+    Json,
+}
+
+/// Double-quoted `.env` value: backslashes, quotes and newlines are escaped, and
+/// `$` becomes `dollar` so it isn't interpolated: `\$` for dotenv, `$$` for compose.
+fn dotenv_line(name: &str, value: &str, dollar: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '$' => escaped.push_str(dollar),
+            c => escaped.push(c),
+        }
+    }
+    format!("{}=\"{}\"", name, escaped)
+}
+
+/// GitHub Actions reads `KEY=value` verbatim up to the end of the line; multiline
+/// values need the `KEY<<DELIMITER` heredoc form with a delimiter absent from the value.
+fn github_line(name: &str, value: &str) -> String {
+    if !value.contains('\n') && !value.contains('\r') {
+        return format!("{}={}", name, value);
+    }
+
+    let mut delimiter = "STARGATE_EOF".to_string();
+    while value.contains(&delimiter) {
+        delimiter.push('_');
+    }
+    format!("{}<<{}\n{}\n{}", name, delimiter, value, delimiter)
+}
+
+/// Docker env files take everything after `=` literally and have no way to
+/// escape a newline, so those values are rejected.
+fn docker_line(name: &str, value: &str) -> Result<String, String> {
+    if value.contains('\n') || value.contains('\r') {
+        return Err(format!(
+            "{} contains a newline, which docker env files cannot represent.",
+            name
+        ));
+    }
+    Ok(format!("{}={}", name, value))
+}
+
+/// Renders the exports in the given format. Variables that `switch` would unset
+/// are left out of every format except `shell`.
+pub fn render(exports: &NetworkExports, format: EnvFormat) -> Result<String, String> {
+    render_vars(exports.vars(), format)
+}
+
+/// Renders `(name, value)` pairs; `None` values become `unset` in `shell` format
+/// and are omitted from the others.
+pub fn render_vars(
+    vars: Vec<(String, Option<String>)>,
+    format: EnvFormat,
+) -> Result<String, String> {
+    let set: Vec<(&str, &str)> = vars
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.as_deref()?)))
+        .collect();
+
+    let lines = match format {
+        EnvFormat::Shell => vars
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => shell_export(name, value),
+                None => format!("unset {}", name),
+            })
+            .collect(),
+        EnvFormat::Json => {
+            let object: serde_json::Map<String, serde_json::Value> = set
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.into()))
+                .collect();
+            return serde_json::to_string_pretty(&object)
+                .map_err(|e| format!("Failed to serialize environment: {}", e));
+        }
+        EnvFormat::Dotenv => set.iter().map(|(n, v)| dotenv_line(n, v, "\\$")).collect(),
+        EnvFormat::Compose => set.iter().map(|(n, v)| dotenv_line(n, v, "$$")).collect(),
+        EnvFormat::Github => set.iter().map(|(n, v)| github_line(n, v)).collect(),
+        EnvFormat::Docker => set
+            .iter()
+            .map(|(n, v)| docker_line(n, v))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    Ok(lines.join("\n"))
+}
+
+pub fn run(network_name: &str, format: EnvFormat) {
+    let config = load_config();
+
+    let Some(network) = find_network(network_name) else {
+        eprintln!("{}", unknown_network_message(network_name));
+        eprintln!("Run 'stargate list' to see available networks.");
+        std::process::exit(1);
+    };
+
+    let api_key = match config.api_key_for(network) {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let exports = NetworkExports::from_network(network, &api_key)
+        .with_settings(network, &config)
+        .with_shell_state(|name| std::env::var(name).ok());
+
+    match render(&exports, format) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exports(rpc_url: &str) -> NetworkExports {
+        NetworkExports {
+            rpc_url: rpc_url.to_string(),
+            network_name: "sepolia".to_string(),
+            chain_id: 11155111,
+            explorer_url: None,
+            protected: false,
+            profile: None,
+            sender: None,
+            account: None,
+            addresses: Vec::new(),
+            stale_addresses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn dotenv_escapes_quotes_backslashes_newlines_and_dollars() {
+        assert_eq!(dotenv_line("X", "a\"b\\c\nd", "\\$"), r#"X="a\"b\\c\nd""#);
+        assert_eq!(
+            dotenv_line("X", "p$ss${HOME}", "\\$"),
+            r#"X="p\$ss\${HOME}""#
+        );
+        assert_eq!(
+            dotenv_line("X", "p$ss${HOME}", "$$"),
+            r#"X="p$$ss$${HOME}""#
+        );
+    }
+
+    #[test]
+    fn github_uses_heredoc_for_multiline_values() {
+        assert_eq!(github_line("X", "plain $value"), "X=plain $value");
+        assert_eq!(
+            github_line("X", "a\nSTARGATE_EOF"),
+            "X<<STARGATE_EOF_\na\nSTARGATE_EOF\nSTARGATE_EOF_"
+        );
+    }
+
+    #[test]
+    fn docker_rejects_newlines() {
+        assert_eq!(docker_line("X", "a b").unwrap(), "X=a b");
+        assert!(docker_line("X", "a\nb").is_err());
+    }
+
+    #[test]
+    fn unset_vars_only_appear_in_shell_format() {
+        let exports = exports("https://example.com");
+        assert!(
+            render(&exports, EnvFormat::Shell)
+                .unwrap()
+                .contains("unset BLOCK_EXPLORER")
+        );
+        let dotenv = render(&exports, EnvFormat::Dotenv).unwrap();
+        assert!(!dotenv.contains("BLOCK_EXPLORER"));
+        assert!(!dotenv.contains("STARGATE_PROTECTED"));
+        assert!(dotenv.contains("STARGATE_CHAIN_ID=\"11155111\""));
+    }
+
+    #[test]
+    fn json_is_an_object_of_set_vars() {
+        let json = render(&exports("https://example.com"), EnvFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["ETH_RPC_URL"], "https://example.com");
+        assert_eq!(value["STARGATE_NETWORK"], "sepolia");
+        assert!(value.get("BLOCK_EXPLORER").is_none());
+    }
+}
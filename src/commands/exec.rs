@@ -0,0 +1,109 @@
+use crate::commands::each::{confirm_protected, run_with_network};
+use crate::commands::load_config;
+use crate::networks::{Network, find_network, unknown_network_message};
+use std::process::{Command, ExitStatus};
+
+fn find_or_exit(network_name: &str) -> &'static Network {
+    find_network(network_name).unwrap_or_else(|| {
+        eprintln!("{}", unknown_network_message(network_name));
+        eprintln!("Run 'stargate list' to see available networks.");
+        std::process::exit(1);
+    })
+}
+
+fn exit_like(result: Result<ExitStatus, String>) -> ! {
+    match result {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs one command with a network's environment, without switching the shell.
+pub fn run(network_name: &str, yes: bool, command: &[String]) {
+    let config = load_config();
+    let network = find_or_exit(network_name);
+    confirm_protected(&config, &[network], yes);
+    exit_like(run_with_network(&config, network, command));
+}
+
+/// The `anvil` command line forking `rpc_url`.
+fn anvil_command(
+    rpc_url: &str,
+    block: Option<u64>,
+    port: Option<u16>,
+    extra: &[String],
+) -> Vec<String> {
+    let mut command = vec![
+        "anvil".to_string(),
+        "--fork-url".to_string(),
+        rpc_url.to_string(),
+    ];
+    if let Some(block) = block {
+        command.extend(["--fork-block-number".to_string(), block.to_string()]);
+    }
+    if let Some(port) = port {
+        command.extend(["--port".to_string(), port.to_string()]);
+    }
+    command.extend(extra.iter().cloned());
+    command
+}
+
+/// Starts anvil forking a network; `sg root` then points the shell at it.
+pub fn fork(network_name: &str, block: Option<u64>, port: Option<u16>, extra: &[String]) {
+    let config = load_config();
+    let network = find_or_exit(network_name);
+
+    let api_key = match config.api_key_for(network) {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!("Forking {} ({}).", network.name, network.chain_id);
+    if port.is_none() {
+        eprintln!("Run 'sg root' in another shell to use the fork.");
+    }
+    let command = anvil_command(&config.rpc_url(network, &api_key), block, port, extra);
+    exit_like(
+        Command::new(&command[0])
+            .args(&command[1..])
+            .status()
+            .map_err(|e| format!("Failed to run anvil: {}. Is Foundry installed?", e)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anvil_command_forks_at_block_and_port() {
+        assert_eq!(
+            anvil_command(
+                "https://rpc",
+                Some(19_000_000),
+                Some(8546),
+                &["--silent".to_string()]
+            ),
+            vec![
+                "anvil",
+                "--fork-url",
+                "https://rpc",
+                "--fork-block-number",
+                "19000000",
+                "--port",
+                "8546",
+                "--silent"
+            ]
+        );
+        assert_eq!(
+            anvil_command("https://rpc", None, None, &[]),
+            vec!["anvil", "--fork-url", "https://rpc"]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::addresses::{AddressBook, normalize_label};
+use crate::networks::{Network, find_network};
 use std::env;
 
 /// Builds the explorer URL based on the target type.
@@ -20,6 +22,16 @@ pub fn build_explorer_url(base_url: &str, target: Option<&str>) -> String {
     }
 }
 
+/// Replaces an address book label with its address on `network`; anything else,
+/// including unknown labels, is passed through for the explorer to search.
+fn resolve_label(target: &str, book: &AddressBook, network: Option<&Network>) -> String {
+    if normalize_label(target).is_err() {
+        return target.to_string();
+    }
+    book.resolve(target, network)
+        .map_or_else(|| target.to_string(), String::from)
+}
+
 pub fn run(target: Option<&str>, print: bool) {
     let network = env::var("STARGATE_NETWORK").ok();
     let Some(explorer) = env::var("BLOCK_EXPLORER").ok() else {
@@ -30,7 +42,14 @@ pub fn run(target: Option<&str>, print: bool) {
         std::process::exit(1);
     };
 
-    let url = build_explorer_url(&explorer, target);
+    let target = target.map(|t| match AddressBook::load() {
+        Ok(book) => resolve_label(t, &book, network.as_deref().and_then(find_network)),
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            t.to_string()
+        }
+    });
+    let url = build_explorer_url(&explorer, target.as_deref());
 
     if print {
         println!("{}", url);
@@ -141,4 +160,23 @@ mod tests {
         let url = build_explorer_url("https://arbiscan.io", Some(address));
         assert_eq!(url, format!("https://arbiscan.io/address/{}", address));
     }
+
+    // ==================== Address book labels ====================
+
+    #[test]
+    fn resolves_labels_for_the_current_network() {
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let mut book = AddressBook::default();
+        book.add("usdc", usdc, find_network("mainnet")).unwrap();
+
+        assert_eq!(resolve_label("USDC", &book, find_network("mainnet")), usdc);
+        assert_eq!(resolve_label("usdc", &book, find_network("base")), "usdc");
+        assert_eq!(resolve_label("12345678", &book, None), "12345678");
+
+        let url = build_explorer_url(
+            BASE_URL,
+            Some(&resolve_label("usdc", &book, find_network("mainnet"))),
+        );
+        assert_eq!(url, format!("{}/address/{}", BASE_URL, usdc));
+    }
 }
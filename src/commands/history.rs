@@ -0,0 +1,47 @@
+use crate::history::{self, format_timestamp};
+
+/// Abbreviates `home` to `~` at the start of `cwd`, on path boundaries only.
+fn tilde(cwd: &str, home: Option<&str>) -> String {
+    match home.and_then(|home| cwd.strip_prefix(home)) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+        _ => cwd.to_string(),
+    }
+}
+
+pub fn run(limit: usize) {
+    let entries = history::load();
+
+    if entries.is_empty() {
+        println!("No switches recorded yet.");
+        return;
+    }
+
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().into_owned());
+    let start = entries.len().saturating_sub(limit);
+
+    for (i, entry) in entries.iter().enumerate().skip(start) {
+        let cwd = tilde(&entry.cwd, home.as_deref());
+
+        println!(
+            "{:>5}  {}  {:<15} {}",
+            i + 1,
+            format_timestamp(entry.timestamp),
+            entry.network,
+            cwd
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilde_only_replaces_whole_home_directory() {
+        let home = Some("/home/al");
+        assert_eq!(tilde("/home/al", home), "~");
+        assert_eq!(tilde("/home/al/src", home), "~/src");
+        assert_eq!(tilde("/home/alice/x", home), "/home/alice/x");
+        assert_eq!(tilde("/tmp", None), "/tmp");
+    }
+}
@@ -0,0 +1,295 @@
+use crate::commands::load_config;
+use crate::config::Config;
+use crate::networks::{Network, find_network, unknown_network_message};
+use crate::output::{OutputFormat, print_serialized};
+use crate::redact::{MASK, redact_url};
+use crate::rpc;
+use serde::Serialize;
+use std::env;
+
+/// Live chain data fetched with `--live`.
+#[derive(Debug, Default, Serialize)]
+struct LiveInfo {
+    block_number: Option<u64>,
+    gas_price_wei: Option<String>,
+    client_version: Option<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NetworkInfo<'a> {
+    name: &'a str,
+    aliases: &'a [&'a str],
+    chain_id: u64,
+    chain_id_hex: String,
+    rpc_url: Option<String>,
+    explorer_url: Option<&'a str>,
+    verifier_url: Option<String>,
+    provider: &'a str,
+    tags: Vec<&'a str>,
+    protected: bool,
+    from: Option<&'a str>,
+    account: Option<&'a str>,
+    current: bool,
+    default: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    live: Option<LiveInfo>,
+}
+
+impl<'a> NetworkInfo<'a> {
+    fn new(network: &'a Network, config: &'a Config, api_key: Option<&str>, reveal: bool) -> Self {
+        let rpc_url = match (config.needs_api_key(network), api_key) {
+            (true, None) => None,
+            (_, api_key) => {
+                let rpc_url = config.rpc_url(network, api_key.unwrap_or(""));
+                Some(if reveal {
+                    rpc_url
+                } else {
+                    redact_url(&rpc_url)
+                })
+            }
+        };
+
+        Self {
+            name: network.name,
+            aliases: network.aliases,
+            chain_id: network.chain_id,
+            chain_id_hex: format!("{:#x}", network.chain_id),
+            rpc_url,
+            explorer_url: network.explorer_url,
+            verifier_url: network.verifier_url(),
+            provider: config.provider_for(network),
+            tags: config.tags_for(network),
+            protected: config.is_protected(network),
+            from: config.sender_for(network),
+            account: config.account_for(network),
+            current: env::var("STARGATE_NETWORK").is_ok_and(|n| n == network.name),
+            default: find_network(config.get_default_network())
+                .is_some_and(|d| d.name == network.name),
+            live: None,
+        }
+    }
+}
+
+fn fetch_live(rpc_url: &str) -> LiveInfo {
+    let mut live = LiveInfo::default();
+
+    match rpc::call(rpc_url, "eth_blockNumber").and_then(|v| rpc::parse_quantity(&v)) {
+        Ok(block) => live.block_number = Some(block as u64),
+        Err(e) => live.errors.push(e),
+    }
+    match rpc::call(rpc_url, "eth_gasPrice").and_then(|v| rpc::parse_quantity(&v)) {
+        Ok(wei) => live.gas_price_wei = Some(wei.to_string()),
+        Err(e) => live.errors.push(e),
+    }
+    match rpc::call(rpc_url, "web3_clientVersion") {
+        Ok(version) => live.client_version = version.as_str().map(String::from),
+        Err(e) => live.errors.push(e),
+    }
+
+    live
+}
+
+fn print_table(info: &NetworkInfo) {
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    let or_dash = |s: Option<&str>| s.unwrap_or("-").to_string();
+
+    let mut rows = vec![
+        ("Name", info.name.to_string()),
+        (
+            "Aliases",
+            if info.aliases.is_empty() {
+                "-".to_string()
+            } else {
+                info.aliases.join(", ")
+            },
+        ),
+        (
+            "Chain ID",
+            format!("{} ({})", info.chain_id, info.chain_id_hex),
+        ),
+        (
+            "RPC URL",
+            info.rpc_url
+                .clone()
+                .unwrap_or_else(|| "- (no API key configured)".to_string()),
+        ),
+        ("Explorer", or_dash(info.explorer_url)),
+        ("Verifier", or_dash(info.verifier_url.as_deref())),
+        ("Provider", info.provider.to_string()),
+        (
+            "Tags",
+            if info.tags.is_empty() {
+                "-".to_string()
+            } else {
+                info.tags.join(", ")
+            },
+        ),
+        ("Protected", yes_no(info.protected).to_string()),
+        ("Sender", or_dash(info.from)),
+        ("Account", or_dash(info.account)),
+        ("Current", yes_no(info.current).to_string()),
+        ("Default", yes_no(info.default).to_string()),
+    ];
+
+    if let Some(live) = &info.live {
+        rows.push((
+            "Latest block",
+            live.block_number.map_or("-".to_string(), |n| n.to_string()),
+        ));
+        rows.push((
+            "Gas price",
+            live.gas_price_wei
+                .as_deref()
+                .and_then(|w| w.parse().ok())
+                .map_or("-".to_string(), rpc::format_gwei),
+        ));
+        rows.push(("Client", or_dash(live.client_version.as_deref())));
+    }
+
+    for (label, value) in rows {
+        println!("{:<14} {}", format!("{}:", label), value);
+    }
+
+    if let Some(live) = &info.live {
+        for error in &live.errors {
+            eprintln!("Warning: {}", error);
+        }
+    }
+}
+
+pub fn run(network_name: &str, live: bool, reveal: bool, format: OutputFormat) {
+    let config = load_config();
+
+    let Some(network) = find_network(network_name) else {
+        eprintln!("{}", unknown_network_message(network_name));
+        eprintln!("Run 'stargate list' to see available networks.");
+        std::process::exit(1);
+    };
+
+    // Only run the key command or open the vault when the key is actually used;
+    // the redacted URL just needs to know that there is one.
+    let api_key = if reveal || live {
+        config.resolve_api_key().unwrap_or_else(|e| {
+            eprintln!("Warning: {}", e);
+            None
+        })
+    } else {
+        config.has_api_key().then(|| MASK.to_string())
+    };
+    let mut info = NetworkInfo::new(network, &config, api_key.as_deref(), reveal);
+
+    if live {
+        if config.needs_api_key(network) && api_key.is_none() {
+            eprintln!("No API key configured. Run 'stargate config set api-key <your-key>' first.");
+            std::process::exit(1);
+        }
+        let rpc_url = config.rpc_url(network, api_key.as_deref().unwrap_or(""));
+        info.live = Some(fetch_live(&rpc_url));
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Toml => print_serialized(&info, format),
+        OutputFormat::Table | OutputFormat::Plain => print_table(&info),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_redacts_api_key() {
+        let config = Config {
+            api_key: Some("super-secret".to_string()),
+            ..Default::default()
+        };
+        let info = NetworkInfo::new(
+            find_network("mainnet").unwrap(),
+            &config,
+            config.api_key.as_deref(),
+            false,
+        );
+
+        let rpc_url = info.rpc_url.unwrap();
+        assert!(!rpc_url.contains("super-secret"));
+        assert_eq!(rpc_url, "https://eth-mainnet.g.alchemy.com/v2/***");
+    }
+
+    #[test]
+    fn info_reveals_api_key_on_request() {
+        let config = Config {
+            api_key: Some("super-secret".to_string()),
+            ..Default::default()
+        };
+        let info = NetworkInfo::new(
+            find_network("mainnet").unwrap(),
+            &config,
+            config.api_key.as_deref(),
+            true,
+        );
+        assert_eq!(
+            info.rpc_url.as_deref(),
+            Some("https://eth-mainnet.g.alchemy.com/v2/super-secret")
+        );
+    }
+
+    #[test]
+    fn info_has_no_rpc_url_without_api_key() {
+        let config = Config::default();
+        let info = NetworkInfo::new(
+            find_network("base").unwrap(),
+            &config,
+            config.api_key.as_deref(),
+            false,
+        );
+        assert!(info.rpc_url.is_none());
+
+        let info = NetworkInfo::new(
+            find_network("anvil").unwrap(),
+            &config,
+            config.api_key.as_deref(),
+            false,
+        );
+        assert_eq!(info.rpc_url.as_deref(), Some("http://127.0.0.1:8545"));
+    }
+
+    #[test]
+    fn info_shows_chain_id_in_hex() {
+        let config = Config::default();
+        let info = NetworkInfo::new(
+            find_network("arbitrum").unwrap(),
+            &config,
+            config.api_key.as_deref(),
+            false,
+        );
+        assert_eq!(info.chain_id, 42161);
+        assert_eq!(info.chain_id_hex, "0xa4b1");
+    }
+
+    #[test]
+    fn info_reports_default_network() {
+        let config = Config {
+            default_network: Some("base".to_string()),
+            ..Default::default()
+        };
+        assert!(
+            NetworkInfo::new(
+                find_network("base").unwrap(),
+                &config,
+                config.api_key.as_deref(),
+                false
+            )
+            .default
+        );
+        assert!(
+            !NetworkInfo::new(
+                find_network("anvil").unwrap(),
+                &config,
+                config.api_key.as_deref(),
+                false
+            )
+            .default
+        );
+    }
+}
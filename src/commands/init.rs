@@ -1,35 +1,192 @@
 use crate::config::Config;
 
-pub fn run(shell: &str) {
+/// Prompt integration appended to the shell integration.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum PromptStyle {
+    /// Prepend the segment to PS1 / PROMPT
+    Ps1,
+    /// Define a powerlevel10k `stargate` segment (zsh only)
+    P10k,
+}
+
+const STARSHIP_MODULE: &str = r#"# Add to ~/.config/starship.toml
+[custom.stargate]
+command = "stargate prompt --color"
+when = 'test -n "$STARGATE_NETWORK"'
+format = "$output "
+"#;
+
+fn prompt_snippet(shell: &str, style: PromptStyle) -> Result<&'static str, String> {
+    match (shell, style) {
+        ("zsh", PromptStyle::Ps1) => Ok(r#"
+# Show the active network in the prompt
+setopt PROMPT_SUBST
+PROMPT='$(command stargate prompt --color --shell zsh --format "[{name}] ")'"$PROMPT"
+"#),
+        ("bash", PromptStyle::Ps1) => Ok(r#"
+# Show the active network in the prompt
+PS1='$(command stargate prompt --color --shell bash --format "[{name}] ")'"$PS1"
+"#),
+        ("zsh", PromptStyle::P10k) => Ok(r#"
+# powerlevel10k segment: add `stargate` to POWERLEVEL9K_{LEFT,RIGHT}_PROMPT_ELEMENTS
+function prompt_stargate() {
+    [[ -n $STARGATE_NETWORK ]] || return
+    local color=cyan
+    [[ $ETH_RPC_URL == *127.0.0.1* || $ETH_RPC_URL == *localhost* ]] && color=244
+    [[ $STARGATE_PROTECTED == 1 ]] && color=red
+    p10k segment -f $color -t "$(command stargate prompt)"
+}
+"#),
+        (_, PromptStyle::P10k) => Err("The p10k prompt is only available for zsh.".to_string()),
+        _ => Err(format!("Unsupported shell: {}", shell)),
+    }
+}
+
+/// Loads completions for the `sg` wrapper at shell startup, so newly imported
+/// networks are picked up by every new shell.
+fn completion_snippet(shell: &str) -> String {
     match shell {
-        "zsh" | "bash" => {
-            let config = Config::load();
-            let default_network = config.get_default_network();
+        "zsh" => r#"
+# Completions for sg (requires compinit)
+if (( $+functions[compdef] )); then
+    source <(command stargate completions zsh --bin sg)
+fi
+"#
+        .to_string(),
+        _ => r#"
+# Completions for sg
+source <(command stargate completions bash --bin sg)
+"#
+        .to_string(),
+    }
+}
+
+/// `sg` evals the output of the commands that change the shell. Global flags such
+/// as `--profile <name>` may come before the subcommand, so they're skipped first.
+const SG_FUNCTION: &str = r#"sg() {
+    local sg_command="" sg_skip="" sg_arg
+    for sg_arg in "$@"; do
+        if [ -n "$sg_skip" ]; then
+            sg_skip=""
+            continue
+        fi
+        case "$sg_arg" in
+            --profile|--format) sg_skip=1 ;;
+            -*) ;;
+            *) sg_command="$sg_arg"; break ;;
+        esac
+    done
 
-            let shell_integration = format!(
-                r#"sg() {{
-    case "$1" in
-        switch|sw|root)
+    case "$sg_command" in
+        switch|sw|root|off|unlock|lock)
             eval "$(command stargate "$@")"
             ;;
         *)
             command stargate "$@"
             ;;
     esac
-}}
+}
+"#;
 
+pub fn run(shell: &str, prompt: Option<PromptStyle>) {
+    match shell {
+        "zsh" | "bash" => {
+            // A broken config must not break shell startup
+            let config = Config::load().unwrap_or_else(|e| {
+                eprintln!("Warning: {}. Using defaults.", e);
+                Config::default()
+            });
+            let default_network = config.get_default_network();
+
+            let mut shell_integration = format!(
+                r#"{}
 # Set default network
-eval "$(command stargate switch {} --silent)"
+eval "$(command stargate switch {} --silent --no-history --yes)"
 "#,
-                default_network
+                SG_FUNCTION, default_network
             );
 
+            shell_integration.push_str(&completion_snippet(shell));
+
+            if let Some(style) = prompt {
+                match prompt_snippet(shell, style) {
+                    Ok(snippet) => shell_integration.push_str(snippet),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
             print!("{}", shell_integration);
         }
+        "starship" => print!("{}", STARSHIP_MODULE),
         _ => {
             eprintln!("Unsupported shell: {}", shell);
-            eprintln!("Supported shells: zsh, bash");
+            eprintln!("Supported shells: zsh, bash (or 'starship' for a prompt module)");
             std::process::exit(1);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    /// Runs `sg` in bash against a fake `stargate` that prints a shell statement.
+    fn run_sg(args: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("stargate-sg-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fake = dir.join("stargate");
+        fs::write(&fake, "#!/bin/sh\necho 'echo evaluated'\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let script = format!("{}\nsg {}", SG_FUNCTION, args);
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(script)
+            .env("PATH", format!("{}:/usr/bin:/bin", dir.display()))
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn sg_evals_switch_after_global_flags() {
+        assert_eq!(run_sg("switch base"), "evaluated");
+        assert_eq!(run_sg("--profile staging switch base"), "evaluated");
+        assert_eq!(run_sg("--profile=staging off"), "evaluated");
+        assert_eq!(run_sg("--profile switch list"), "echo evaluated");
+        assert_eq!(run_sg("list"), "echo evaluated");
+    }
+
+    #[test]
+    fn ps1_snippets_use_shell_specific_escaping() {
+        assert!(
+            prompt_snippet("zsh", PromptStyle::Ps1)
+                .unwrap()
+                .contains("--shell zsh")
+        );
+        assert!(
+            prompt_snippet("bash", PromptStyle::Ps1)
+                .unwrap()
+                .contains("--shell bash")
+        );
+    }
+
+    #[test]
+    fn p10k_snippet_is_zsh_only() {
+        assert!(
+            prompt_snippet("zsh", PromptStyle::P10k)
+                .unwrap()
+                .contains("p10k segment")
+        );
+        assert!(prompt_snippet("bash", PromptStyle::P10k).is_err());
+    }
+}
@@ -1,24 +1,277 @@
-use crate::networks::NETWORKS;
+use crate::commands::load_config;
+use crate::config::Config;
+use crate::networks::{Network, all_networks};
+use crate::output::{NetworkView, OutputFormat, print_serialized};
+use crate::picker;
+use crate::redact::{MASK, redact_url};
+use crossterm::style::Stylize;
+use serde::Serialize;
+use std::env;
+use std::io::{self, IsTerminal};
 
-pub fn run() {
-    println!(
-        "{:<20} {:<15} {:>10}   Explorer",
-        "Network", "Aliases", "Chain ID"
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Column {
+    Name,
+    Aliases,
+    #[value(name = "chain_id", alias = "chain-id")]
+    ChainId,
+    Rpc,
+    Explorer,
+    Provider,
+    Tags,
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Name => "Network",
+            Column::Aliases => "Aliases",
+            Column::ChainId => "Chain ID",
+            Column::Rpc => "RPC",
+            Column::Explorer => "Explorer",
+            Column::Provider => "Provider",
+            Column::Tags => "Tags",
+        }
+    }
+
+    fn cell(self, network: &Network, config: &Config) -> String {
+        let or_dash = |s: String| if s.is_empty() { "-".to_string() } else { s };
+
+        match self {
+            Column::Name => network.name.to_string(),
+            Column::Aliases => or_dash(network.aliases.join(", ")),
+            Column::ChainId => network.chain_id.to_string(),
+            Column::Rpc => redact_url(&config.rpc_url(network, MASK)),
+            Column::Explorer => network.explorer_url.unwrap_or("-").to_string(),
+            Column::Provider => provider_status(network, config),
+            Column::Tags => or_dash(config.tags_for(network).join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum SortKey {
+    Name,
+    ChainId,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ListArgs {
+    /// Only show networks whose name, alias, chain ID or tags contain this text
+    query: Option<String>,
+    /// Only show networks with this tag (e.g. a family such as op-stack)
+    #[arg(short, long, visible_alias = "family")]
+    tag: Option<String>,
+    /// Only show networks in this group
+    #[arg(short, long, conflicts_with = "tag")]
+    group: Option<String>,
+    /// Sort order (defaults to registry order)
+    #[arg(long, value_enum)]
+    sort: Option<SortKey>,
+    /// Columns to show (comma-separated)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "name,aliases,chain_id,explorer"
+    )]
+    columns: Vec<Column>,
+    /// Only show networks that can be used with the current configuration
+    #[arg(long)]
+    only_configured: bool,
+}
+
+#[derive(Serialize)]
+struct NetworkList<'a> {
+    networks: Vec<NetworkView<'a>>,
+}
+
+/// Whether `network` can be switched to with the current configuration.
+pub(crate) fn is_configured(network: &Network, config: &Config) -> bool {
+    !config.needs_api_key(network) || config.has_api_key()
+}
+
+fn provider_status(network: &Network, config: &Config) -> String {
+    if is_configured(network, config) {
+        config.provider_for(network).to_string()
+    } else {
+        format!("{} (no API key)", config.provider_for(network))
+    }
+}
+
+/// Lays out rows in left-aligned columns sized to their content.
+pub(crate) fn render_table(headers: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].chars().count())
+                .chain(std::iter::once(headers[i].len()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("   ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(headers.to_vec())];
+    lines.push("-".repeat(lines[0].len()));
+    lines.extend(
+        rows.iter()
+            .map(|r| line(r.iter().map(|c| c.as_str()).collect())),
     );
-    println!("{}", "-".repeat(75));
+    lines
+}
 
-    for network in NETWORKS.iter() {
-        let aliases = if network.aliases.is_empty() {
-            "-".to_string()
+pub fn run(args: ListArgs, format: OutputFormat) {
+    let config = load_config();
+
+    let mut networks: Vec<&Network> = match (args.tag.as_deref(), args.group.as_deref()) {
+        (_, Some(group)) => match config.group(group) {
+            Ok(networks) => networks,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        (Some(tag), None) => config.networks_with_tag(tag),
+        (None, None) => all_networks().collect(),
+    };
+
+    if networks.is_empty()
+        && let Some(tag) = args.tag.as_deref()
+    {
+        eprintln!("No networks tagged '{}'.", tag);
+        std::process::exit(1);
+    }
+
+    if let Some(query) = args.query.as_deref() {
+        networks = picker::filter(&networks, query, &config);
+    }
+    if args.only_configured {
+        networks.retain(|n| is_configured(n, &config));
+    }
+    match args.sort {
+        Some(SortKey::Name) => networks.sort_by_key(|n| n.name),
+        Some(SortKey::ChainId) => networks.sort_by_key(|n| n.chain_id),
+        None => {}
+    }
+
+    match format {
+        OutputFormat::Json => {
+            let views: Vec<NetworkView> = networks
+                .iter()
+                .map(|n| NetworkView::new(n, &config))
+                .collect();
+            return print_serialized(&views, format);
+        }
+        OutputFormat::Toml => {
+            let list = NetworkList {
+                networks: networks
+                    .iter()
+                    .map(|n| NetworkView::new(n, &config))
+                    .collect(),
+            };
+            return print_serialized(&list, format);
+        }
+        OutputFormat::Plain => {
+            for network in networks {
+                let cells: Vec<String> = args
+                    .columns
+                    .iter()
+                    .map(|c| c.cell(network, &config))
+                    .collect();
+                println!("{}", cells.join("\t"));
+            }
+            return;
+        }
+        OutputFormat::Table => {}
+    }
+
+    let rows: Vec<Vec<String>> = networks
+        .iter()
+        .map(|n| args.columns.iter().map(|c| c.cell(n, &config)).collect())
+        .collect();
+    let headers: Vec<&str> = args.columns.iter().map(|c| c.header()).collect();
+    let lines = render_table(&headers, &rows);
+
+    println!("  {}", lines[0]);
+    println!("--{}", lines[1]);
+
+    // Mark the active network, in bold when printing to a terminal
+    let active = env::var("STARGATE_NETWORK").ok();
+    let highlight = io::stdout().is_terminal();
+    for (network, line) in networks.iter().zip(&lines[2..]) {
+        if active.as_deref() != Some(network.name) {
+            println!("  {}", line);
+        } else if highlight {
+            println!("{}", format!("* {}", line).bold());
         } else {
-            network.aliases.join(", ")
+            println!("* {}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::find_network;
+
+    #[test]
+    fn alchemy_networks_need_an_api_key() {
+        let mainnet = find_network("mainnet").unwrap();
+        let anvil = find_network("anvil").unwrap();
+
+        let config = Config::default();
+        assert!(!is_configured(mainnet, &config));
+        assert!(is_configured(anvil, &config));
+        assert_eq!(provider_status(mainnet, &config), "alchemy (no API key)");
+        assert_eq!(provider_status(anvil, &config), "local");
+
+        let config = Config {
+            api_key: Some("key".to_string()),
+            ..Default::default()
         };
+        assert!(is_configured(mainnet, &config));
+        assert_eq!(provider_status(mainnet, &config), "alchemy");
+    }
 
-        let explorer = network.explorer_url.unwrap_or("-");
+    #[test]
+    fn cells_render_each_column() {
+        let config = Config::default();
+        let mainnet = find_network("mainnet").unwrap();
+        let base = find_network("base").unwrap();
 
-        println!(
-            "{:<20} {:<15} {:>10}   {}",
-            network.name, aliases, network.chain_id, explorer
+        assert_eq!(Column::Name.cell(mainnet, &config), "mainnet");
+        assert_eq!(Column::Aliases.cell(mainnet, &config), "eth, ethereum");
+        assert_eq!(Column::Aliases.cell(base, &config), "-");
+        assert_eq!(Column::ChainId.cell(mainnet, &config), "1");
+        assert_eq!(
+            Column::Rpc.cell(mainnet, &config),
+            "https://eth-mainnet.g.alchemy.com/v2/***"
         );
+        assert_eq!(Column::Tags.cell(mainnet, &config), "-");
+    }
+
+    #[test]
+    fn table_columns_fit_their_content() {
+        let rows = vec![
+            vec!["mainnet".to_string(), "1".to_string()],
+            vec!["polygon-zkevm".to_string(), "1101".to_string()],
+        ];
+        let lines = render_table(&["Network", "Chain ID"], &rows);
+
+        assert_eq!(lines[0], "Network         Chain ID");
+        assert_eq!(lines[1], "-".repeat(lines[0].len()));
+        assert_eq!(lines[2], "mainnet         1");
+        assert_eq!(lines[3], "polygon-zkevm   1101");
     }
 }
@@ -1,7 +1,129 @@
+pub mod addr;
+pub mod completions;
 pub mod config;
 pub mod current;
 pub mod current_rpc;
+pub mod doctor;
+pub mod each;
+pub mod env;
+pub mod exec;
 pub mod explorer;
+pub mod history;
+pub mod info;
 pub mod init;
 pub mod list;
+pub mod off;
+pub mod ping;
+pub mod prompt;
+pub mod registry;
 pub mod switch;
+pub mod vault;
+
+use crate::config::Config;
+use crate::networks::{Network, find_network, unknown_network_message};
+
+/// Loads the config, exiting on read or parse errors instead of running with defaults.
+pub fn load_config() -> Config {
+    match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Run 'stargate config doctor' to diagnose.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints `message` and exits with status 1.
+pub fn exit_with(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// Networks targeted by commands that act on several chains at once.
+#[derive(Debug, clap::Args)]
+pub struct NetworkSet {
+    /// Networks by name, alias or chain ID (comma-separated or repeated)
+    #[arg(short, long = "network", value_delimiter = ',')]
+    networks: Vec<String>,
+    /// Every network with this tag
+    #[arg(short, long, conflicts_with = "networks")]
+    tag: Option<String>,
+    /// Every network in this group from config.toml
+    #[arg(short, long, conflicts_with_all = ["networks", "tag"])]
+    group: Option<String>,
+}
+
+impl NetworkSet {
+    /// Resolves the selection, without duplicates; `None` when nothing was selected.
+    pub fn resolve(&self, config: &Config) -> Result<Option<Vec<&'static Network>>, String> {
+        let mut networks = if let Some(group) = &self.group {
+            config.group(group)?
+        } else if let Some(tag) = &self.tag {
+            let networks = config.networks_with_tag(tag);
+            if networks.is_empty() {
+                return Err(format!("No networks tagged '{}'.", tag));
+            }
+            networks
+        } else if self.networks.is_empty() {
+            return Ok(None);
+        } else {
+            self.networks
+                .iter()
+                .map(|name| find_network(name).ok_or_else(|| unknown_network_message(name)))
+                .collect::<Result<_, _>>()?
+        };
+
+        let mut seen = Vec::new();
+        networks.retain(|n| {
+            let first = !seen.contains(&n.name);
+            seen.push(n.name);
+            first
+        });
+        Ok(Some(networks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(networks: &[&str], tag: Option<&str>, group: Option<&str>) -> NetworkSet {
+        NetworkSet {
+            networks: networks.iter().map(|n| n.to_string()).collect(),
+            tag: tag.map(String::from),
+            group: group.map(String::from),
+        }
+    }
+
+    fn names(networks: Vec<&Network>) -> Vec<&str> {
+        networks.iter().map(|n| n.name).collect()
+    }
+
+    #[test]
+    fn network_set_resolves_groups_tags_and_names() {
+        let config: Config = toml::from_str(
+            "[networks.base]\ntags = [\"l2\"]\n\n[groups]\nd = [\"eth\", \"base\", \"1\"]\n",
+        )
+        .unwrap();
+
+        let group = set(&[], None, Some("d")).resolve(&config).unwrap().unwrap();
+        assert_eq!(names(group), vec!["mainnet", "base"]);
+
+        let tagged = set(&[], Some("l2"), None)
+            .resolve(&config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(names(tagged), vec!["base"]);
+
+        let listed = set(&["arb", "8453"], None, None)
+            .resolve(&config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(names(listed), vec!["arbitrum", "base"]);
+
+        assert!(set(&[], None, None).resolve(&config).unwrap().is_none());
+        assert!(set(&[], Some("nope"), None).resolve(&config).is_err());
+        assert!(set(&["nope"], None, None).resolve(&config).is_err());
+    }
+}
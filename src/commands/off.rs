@@ -0,0 +1,111 @@
+use crate::addresses::{TRACKING_VAR, tracked_vars};
+use crate::commands::switch::{MANAGED_VARS, RESTORED_VARS, original_var, shell_export};
+use std::env;
+
+/// Shell statements that undo a switch, restoring the `RESTORED_VARS` saved before the
+/// first one (`original` looks them up by name). `extra` are further variables to unset,
+/// such as exported address labels.
+fn off_statements(
+    current: Option<&str>,
+    original: impl Fn(&str) -> Option<String>,
+    extra: &[String],
+) -> String {
+    let mut vars: Vec<&str> = MANAGED_VARS.to_vec();
+    vars.extend(extra.iter().map(String::as_str));
+    let mut lines = vec![format!("unset {}", vars.join(" "))];
+
+    for name in RESTORED_VARS {
+        match original(name) {
+            Some(value) => {
+                lines.push(shell_export(name, &value));
+                lines.push(format!("unset {}", original_var(name)));
+            }
+            None => lines.push(format!("unset {}", name)),
+        }
+    }
+
+    if let Some(current) = current {
+        lines.push(shell_export("STARGATE_PREVIOUS_NETWORK", current));
+    }
+
+    lines.join("\n")
+}
+
+pub fn run() {
+    let Some(current) = env::var("STARGATE_NETWORK").ok().filter(|n| !n.is_empty()) else {
+        eprintln!("Stargate is not active.");
+        return;
+    };
+    let original = |name: &str| env::var(original_var(name)).ok();
+
+    let address_vars = tracked_vars(&env::var(TRACKING_VAR).unwrap_or_default());
+
+    println!(
+        "{}",
+        off_statements(Some(&current), original, &address_vars)
+    );
+
+    match original("ETH_RPC_URL") {
+        Some(_) => eprintln!("Left {}. Restored previous ETH_RPC_URL.", current),
+        None => eprintln!("Left {}.", current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsets_every_managed_variable() {
+        let output = off_statements(Some("mainnet"), |_| None, &[]);
+        for var in MANAGED_VARS {
+            assert!(output.contains(var), "Missing unset for {}", var);
+        }
+        assert!(output.contains("unset ETH_RPC_URL"));
+    }
+
+    #[test]
+    fn restores_original_rpc_url() {
+        let original = |name: &str| match name {
+            "ETH_RPC_URL" => Some("https://my-node.example.com".to_string()),
+            "ETH_FROM" => Some("0xmine".to_string()),
+            _ => None,
+        };
+        let output = off_statements(Some("mainnet"), original, &[]);
+        assert!(output.contains("export ETH_RPC_URL=\"https://my-node.example.com\""));
+        assert!(output.contains("unset STARGATE_ORIG_ETH_RPC_URL"));
+        assert!(!output.contains("unset ETH_RPC_URL"));
+        assert!(output.contains("export ETH_FROM=\"0xmine\""));
+        assert!(output.contains("unset STARGATE_ORIG_ETH_FROM"));
+        assert!(output.contains("unset ETH_KEYSTORE_ACCOUNT"));
+    }
+
+    #[test]
+    fn remembers_network_for_switch_back() {
+        let output = off_statements(Some("base"), |_| None, &[]);
+        assert!(output.contains("export STARGATE_PREVIOUS_NETWORK=\"base\""));
+    }
+
+    #[test]
+    fn off_output_is_valid_shell_syntax() {
+        let output = off_statements(
+            Some("base"),
+            |_| Some("http://localhost:8545".to_string()),
+            &[],
+        );
+        for line in output.lines() {
+            assert!(
+                line.starts_with("export ") || line.starts_with("unset "),
+                "Invalid shell line: {}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn unsets_exported_address_labels() {
+        let output = off_statements(None, |_| None, &["ADDR_USDC".to_string()]);
+        assert!(output.starts_with("unset STARGATE_NETWORK"));
+        assert!(output.lines().next().unwrap().ends_with(" ADDR_USDC"));
+    }
+}
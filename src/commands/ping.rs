@@ -0,0 +1,105 @@
+use crate::commands::list::is_configured;
+use crate::commands::{NetworkSet, load_config};
+use crate::config::Config;
+use crate::networks::{Network, all_networks};
+use crate::output::{OutputFormat, print_serialized};
+use crate::rpc;
+use serde::Serialize;
+use std::thread;
+use std::time::Instant;
+
+#[derive(Debug, Serialize)]
+struct PingResult {
+    network: &'static str,
+    chain_id: u64,
+    /// Round trip of `eth_chainId`, when it answered
+    latency_ms: Option<u128>,
+    /// Why the network is not healthy
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PingList {
+    networks: Vec<PingResult>,
+}
+
+fn ping(config: &Config, network: &'static Network) -> PingResult {
+    let mut result = PingResult {
+        network: network.name,
+        chain_id: network.chain_id,
+        latency_ms: None,
+        error: None,
+    };
+
+    let api_key = match config.api_key_for(network) {
+        Ok(key) => key,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+
+    let start = Instant::now();
+    match rpc::call(&config.rpc_url(network, &api_key), "eth_chainId")
+        .and_then(|v| rpc::parse_quantity(&v))
+    {
+        Ok(chain_id) => {
+            result.latency_ms = Some(start.elapsed().as_millis());
+            if chain_id != u128::from(network.chain_id) {
+                result.error = Some(format!("RPC reports chain ID {}", chain_id));
+            }
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+/// Checks that each selected network's RPC answers with the expected chain ID.
+/// Without a selection, every network usable with the current config is checked.
+pub fn run(set: &NetworkSet, format: OutputFormat) {
+    let config = load_config();
+    let networks: Vec<&'static Network> = match set.resolve(&config) {
+        Ok(Some(networks)) => networks,
+        Ok(None) => all_networks()
+            .filter(|n| is_configured(n, &config))
+            .collect(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let results: Vec<PingResult> = thread::scope(|scope| {
+        let handles: Vec<_> = networks
+            .iter()
+            .map(|&network| scope.spawn(|| ping(&config, network)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let healthy = results.iter().all(|r| r.error.is_none());
+
+    match format {
+        OutputFormat::Json => print_serialized(&results, format),
+        OutputFormat::Toml => print_serialized(&PingList { networks: results }, format),
+        OutputFormat::Plain | OutputFormat::Table => {
+            for r in &results {
+                let latency = r
+                    .latency_ms
+                    .map_or("-".to_string(), |ms| format!("{} ms", ms));
+                let status = r.error.as_deref().unwrap_or("ok");
+                if format == OutputFormat::Plain {
+                    println!("{}\t{}\t{}\t{}", r.network, r.chain_id, latency, status);
+                } else {
+                    println!(
+                        "{:<12} {:>10} {:>8}   {}",
+                        r.network, r.chain_id, latency, status
+                    );
+                }
+            }
+        }
+    }
+
+    if !healthy {
+        std::process::exit(1);
+    }
+}
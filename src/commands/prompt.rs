@@ -0,0 +1,155 @@
+use crate::config::Config;
+use crate::networks::find_network;
+use std::env;
+
+/// Shell the segment is embedded in, so escape codes don't break line-width accounting.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum PromptShell {
+    Zsh,
+    Bash,
+    /// Raw escape codes (Starship, p10k, tmux, ...)
+    Raw,
+}
+
+/// SGR code for a named color, as accepted in `[networks.<name>] color = "..."`.
+fn sgr(color: &str) -> Option<&'static str> {
+    Some(match color {
+        "black" => "30",
+        "red" => "31",
+        "green" => "32",
+        "yellow" => "33",
+        "blue" => "34",
+        "magenta" => "35",
+        "cyan" => "36",
+        "white" => "37",
+        "dim" => "2",
+        "bold" => "1",
+        _ => return None,
+    })
+}
+
+/// Substitutes `{name}`, `{chain_id}` and `{profile}` in the user's template.
+fn render(template: &str, name: &str, chain_id: &str, profile: &str) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{chain_id}", chain_id)
+        .replace("{profile}", profile)
+}
+
+/// Wraps `text` in the escape sequence for `color`, marked as zero-width for the shell.
+fn colorize(text: &str, color: &str, shell: PromptShell) -> String {
+    let Some(code) = sgr(color) else {
+        return text.to_string();
+    };
+
+    let (start, end) = (format!("\x1b[{}m", code), "\x1b[0m");
+    match shell {
+        PromptShell::Zsh => format!("%{{{}%}}{}%{{{}%}}", start, text, end),
+        PromptShell::Bash => format!("\x01{}\x02{}\x01{}\x02", start, text, end),
+        PromptShell::Raw => format!("{}{}{}", start, text, end),
+    }
+}
+
+/// Picks the segment color: an explicit per-network color wins, then red for
+/// protected networks and dim for local ones.
+fn color_for(configured: Option<&str>, protected: bool, rpc_url: Option<&str>) -> Option<String> {
+    if let Some(color) = configured {
+        return Some(color.to_string());
+    }
+    if protected {
+        return Some("red".to_string());
+    }
+    let local = rpc_url.is_some_and(|url| url.contains("127.0.0.1") || url.contains("localhost"));
+    local.then(|| "dim".to_string())
+}
+
+pub fn run(format: &str, color: bool, shell: PromptShell) {
+    // An inactive stargate prints nothing so the segment disappears
+    let Some(name) = env::var("STARGATE_NETWORK").ok().filter(|n| !n.is_empty()) else {
+        return;
+    };
+    let chain_id = env::var("STARGATE_CHAIN_ID").unwrap_or_default();
+    let profile = env::var("STARGATE_PROFILE").unwrap_or_default();
+    let segment = render(format, &name, &chain_id, &profile);
+
+    if !color {
+        print!("{}", segment);
+        return;
+    }
+
+    let config = Config::load().unwrap_or_default();
+    let configured = find_network(&name).and_then(|n| config.color_for(n));
+    let protected = env::var("STARGATE_PROTECTED").is_ok_and(|p| p == "1");
+    let rpc_url = env::var("ETH_RPC_URL").ok();
+
+    match color_for(configured, protected, rpc_url.as_deref()) {
+        Some(c) => print!("{}", colorize(&segment, &c, shell)),
+        None => print!("{}", segment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        assert_eq!(render("{name}", "base", "8453", ""), "base");
+        assert_eq!(render("{name}:{chain_id}", "base", "8453", ""), "base:8453");
+        assert_eq!(render("[eth] ", "base", "8453", ""), "[eth] ");
+        assert_eq!(
+            render("{profile}@{name}", "base", "8453", "staging"),
+            "staging@base"
+        );
+    }
+
+    #[test]
+    fn configured_color_wins() {
+        assert_eq!(
+            color_for(Some("magenta"), true, Some("http://127.0.0.1:8545")),
+            Some("magenta".to_string())
+        );
+    }
+
+    #[test]
+    fn protected_networks_are_red() {
+        assert_eq!(color_for(None, true, None), Some("red".to_string()));
+    }
+
+    #[test]
+    fn local_networks_are_dim() {
+        assert_eq!(
+            color_for(None, false, Some("http://127.0.0.1:8545")),
+            Some("dim".to_string())
+        );
+        assert_eq!(
+            color_for(None, false, Some("http://localhost:8545")),
+            Some("dim".to_string())
+        );
+        assert_eq!(
+            color_for(None, false, Some("https://rpc.example.com")),
+            None
+        );
+    }
+
+    #[test]
+    fn colorize_marks_escapes_as_zero_width() {
+        assert_eq!(
+            colorize("base", "red", PromptShell::Zsh),
+            "%{\x1b[31m%}base%{\x1b[0m%}"
+        );
+        assert_eq!(
+            colorize("base", "red", PromptShell::Bash),
+            "\x01\x1b[31m\x02base\x01\x1b[0m\x02"
+        );
+        assert_eq!(
+            colorize("base", "red", PromptShell::Raw),
+            "\x1b[31mbase\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn unknown_colors_are_ignored() {
+        assert_eq!(colorize("base", "chartreuse", PromptShell::Raw), "base");
+    }
+}
@@ -0,0 +1,59 @@
+use crate::registry::{Registry, parse_chainlist};
+use std::fs;
+
+pub fn import(file: &str, chain_ids: &[u64], dry_run: bool) {
+    let json = match fs::read_to_string(file) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", file, e);
+            std::process::exit(1);
+        }
+    };
+
+    let (entries, mut report) = match parse_chainlist(&json, chain_ids) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut registry = match Registry::load() {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    registry.import(entries, &mut report);
+
+    if !dry_run
+        && !report.added.is_empty()
+        && let Err(e) = registry.save()
+    {
+        eprintln!("Failed to save registry: {}", e);
+        std::process::exit(1);
+    }
+
+    for entry in &report.added {
+        println!(
+            "+ {:<30} {:>10}   {}",
+            entry.name, entry.chain_id, entry.rpc_url
+        );
+    }
+    for (name, reason) in &report.skipped {
+        println!("- {:<30} skipped: {}", name, reason);
+    }
+    for chain_id in &report.missing_chain_ids {
+        println!("? chain {} not found in {}", chain_id, file);
+    }
+
+    let verb = if dry_run { "Would add" } else { "Added" };
+    println!(
+        "{} {} network(s), skipped {}.",
+        verb,
+        report.added.len(),
+        report.skipped.len()
+    );
+}
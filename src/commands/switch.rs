@@ -1,13 +1,57 @@
-use crate::config::Config;
-use crate::networks::{Network, find_network};
+use crate::addresses::{AddressBook, TRACKING_VAR, tracked_vars};
+use crate::commands::load_config;
+use crate::config::{Config, Provider};
+use crate::history;
+use crate::networks::{
+    LOCAL_RPC_URL, Network, find_network, find_network_fuzzy, is_address, unknown_network_message,
+};
+use crate::picker;
+use crossterm::style::Stylize;
+use serde::Serialize;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Variables owned by stargate, cleared again by `off`.
+pub const MANAGED_VARS: &[&str] = &[
+    "STARGATE_NETWORK",
+    "STARGATE_CHAIN_ID",
+    "BLOCK_EXPLORER",
+    "STARGATE_PROTECTED",
+    "STARGATE_ACCOUNT",
+    TRACKING_VAR,
+];
+
+/// Variables the user may have set themselves: the first switch saves their value
+/// as `STARGATE_ORIG_<NAME>`, and `off` restores it.
+pub const RESTORED_VARS: &[&str] = &[
+    "ETH_RPC_URL",
+    "ETH_FROM",
+    "ETH_KEYSTORE_ACCOUNT",
+    "STARGATE_PROFILE",
+];
+
+/// `STARGATE_ORIG_<NAME>`, holding the user's own value of `name`.
+pub fn original_var(name: &str) -> String {
+    format!("STARGATE_ORIG_{}", name)
+}
 
 /// Represents the shell exports to be generated when switching networks.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct NetworkExports {
     pub rpc_url: String,
     pub network_name: String,
     pub chain_id: u64,
     pub explorer_url: Option<String>,
+    pub protected: bool,
+    pub profile: Option<String>,
+    pub sender: Option<String>,
+    pub account: Option<String>,
+    /// `ADDR_<LABEL>` variables from the address book, when enabled
+    #[serde(skip)]
+    pub addresses: Vec<(String, String)>,
+    /// `ADDR_<LABEL>` variables the previous switch exported that are gone now
+    #[serde(skip)]
+    pub stale_addresses: Vec<String>,
 }
 
 impl NetworkExports {
@@ -18,52 +62,325 @@ impl NetworkExports {
             network_name: network.name.to_string(),
             chain_id: network.chain_id,
             explorer_url: network.explorer_url.map(|s| s.to_string()),
+            protected: false,
+            profile: None,
+            sender: None,
+            account: None,
+            addresses: Vec::new(),
+            stale_addresses: Vec::new(),
+        }
+    }
+
+    /// Reads back the exports of the active network from the environment.
+    pub fn from_env() -> Option<Self> {
+        let network_name = env::var("STARGATE_NETWORK")
+            .ok()
+            .filter(|n| !n.is_empty())?;
+        let chain_id = env::var("STARGATE_CHAIN_ID").ok()?.parse().ok()?;
+
+        Some(Self {
+            rpc_url: env::var("ETH_RPC_URL").unwrap_or_default(),
+            network_name,
+            chain_id,
+            explorer_url: env::var("BLOCK_EXPLORER").ok(),
+            protected: env::var("STARGATE_PROTECTED").is_ok_and(|p| p == "1"),
+            profile: env::var("STARGATE_PROFILE").ok().filter(|p| !p.is_empty()),
+            sender: env::var("ETH_FROM").ok().filter(|s| !s.is_empty()),
+            account: env::var("STARGATE_ACCOUNT").ok().filter(|a| !a.is_empty()),
+            addresses: Vec::new(),
+            stale_addresses: Vec::new(),
+        })
+    }
+
+    /// Applies per-network settings and the selected profile from the user's config.
+    pub fn with_settings(mut self, network: &Network, config: &Config) -> Self {
+        if config.provider() == Provider::Local {
+            self.rpc_url = LOCAL_RPC_URL.to_string();
         }
+        self.protected = config.is_protected(network);
+        self.profile = config.profile.clone();
+        self.sender = match config.sender_for(network) {
+            Some(from) if !is_address(from) => {
+                eprintln!(
+                    "Warning: from '{}' for {} is not an address; not exporting ETH_FROM.",
+                    from, network.name
+                );
+                None
+            }
+            from => from.map(String::from),
+        };
+        self.account = config.account_for(network).map(String::from);
+        if config.exports_addresses() {
+            match AddressBook::load() {
+                Ok(book) => self.addresses = book.env_vars(Some(network)),
+                Err(e) => eprintln!("Warning: {}", e),
+            }
+        }
+        self
+    }
+
+    /// Takes over state from the shell (read via `var`): the previously exported
+    /// `ADDR_<LABEL>` variables, unset unless exported again. Call after `with_settings`.
+    pub fn with_shell_state(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        self.stale_addresses = tracked_vars(&var(TRACKING_VAR).unwrap_or_default())
+            .into_iter()
+            .filter(|name| !self.addresses.iter().any(|(exported, _)| exported == name))
+            .collect();
+        self
+    }
+
+    /// The variables to set, in output order; `None` means the variable must be unset.
+    pub fn vars(&self) -> Vec<(String, Option<String>)> {
+        let vars = [
+            ("ETH_RPC_URL", Some(self.rpc_url.clone())),
+            ("STARGATE_NETWORK", Some(self.network_name.clone())),
+            ("STARGATE_CHAIN_ID", Some(self.chain_id.to_string())),
+            ("BLOCK_EXPLORER", self.explorer_url.clone()),
+            (
+                "STARGATE_PROTECTED",
+                self.protected.then(|| "1".to_string()),
+            ),
+            // Keeps the profile for later commands in this shell
+            ("STARGATE_PROFILE", self.profile.clone()),
+            // Unset on networks without them, so one network's signer never
+            // carries over to another; `off` restores the user's own
+            ("ETH_FROM", self.sender.clone()),
+            ("STARGATE_ACCOUNT", self.account.clone()),
+            ("ETH_KEYSTORE_ACCOUNT", self.account.clone()),
+        ];
+        let tracked: Vec<&str> = self
+            .addresses
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let tracked = (!tracked.is_empty()).then(|| tracked.join(" "));
+
+        vars.into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .chain([(TRACKING_VAR.to_string(), tracked)])
+            .chain(
+                self.addresses
+                    .iter()
+                    .map(|(name, address)| (name.clone(), Some(address.clone()))),
+            )
+            .chain(self.stale_addresses.iter().map(|name| (name.clone(), None)))
+            .collect()
     }
 
     /// Formats the exports as shell export statements.
     pub fn to_shell_exports(&self) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("export ETH_RPC_URL=\"{}\"\n", self.rpc_url));
-        output.push_str(&format!(
-            "export STARGATE_NETWORK=\"{}\"\n",
-            self.network_name
-        ));
-        output.push_str(&format!("export STARGATE_CHAIN_ID=\"{}\"\n", self.chain_id));
+        self.vars()
+            .into_iter()
+            .map(|(name, value)| match value {
+                Some(value) => shell_export(&name, &value),
+                None => format!("unset {}", name),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
-        if let Some(ref explorer) = self.explorer_url {
-            output.push_str(&format!("export BLOCK_EXPLORER=\"{}\"", explorer));
-        } else {
-            output.push_str("unset BLOCK_EXPLORER");
+/// `export NAME="value"`, escaping everything a double-quoted shell string expands.
+pub fn shell_export(name: &str, value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            escaped.push('\\');
         }
+        escaped.push(c);
+    }
+    format!("export {}=\"{}\"", name, escaped)
+}
+
+/// Flags shared by `switch` and `root`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SwitchOptions {
+    /// Suppress the "Moved to ..." message and the protected-network banner
+    pub silent: bool,
+    /// Accept unique prefixes of network names
+    pub fuzzy: bool,
+    /// Don't append this switch to the history file (used for shell startup)
+    pub no_history: bool,
+    /// Skip the confirmation for protected networks
+    pub yes: bool,
+}
+
+/// Export line remembering the network we are leaving, so `switch -` can go back.
+fn previous_network_export(current: Option<&str>, target: &str) -> Option<String> {
+    match current {
+        Some(current) if !current.is_empty() && current != target => {
+            Some(shell_export("STARGATE_PREVIOUS_NETWORK", current))
+        }
+        _ => None,
+    }
+}
 
-        output
+/// On the first switch of a session, saves the user's own value of a `RESTORED_VARS`
+/// entry so `off` can restore it.
+fn original_export(name: &str, current: Option<&str>, value: Option<&str>) -> Option<String> {
+    match (current, value) {
+        (None, Some(value)) if !value.is_empty() => Some(shell_export(&original_var(name), value)),
+        _ => None,
     }
 }
 
-pub fn run(network_name: &str, silent: bool) {
-    let config = Config::load();
+/// Resolves `switch -`: the exported previous network, falling back to the history file.
+fn resolve_previous() -> Result<String, String> {
+    let current = env::var("STARGATE_NETWORK").ok();
+
+    env::var("STARGATE_PREVIOUS_NETWORK")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .or_else(|| {
+            history::previous_network(&history::load(), current.as_deref()).map(String::from)
+        })
+        .ok_or_else(|| "No previous network to switch back to.".to_string())
+}
+
+pub fn run(network_name: Option<&str>, options: SwitchOptions) {
+    let config = load_config();
+
+    let Some(network_name) = network_name else {
+        match picker::pick(&config) {
+            Ok(Some(network)) => return activate(&config, network, options),
+            Ok(None) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{}", e);
+                eprintln!("Usage: stargate switch <network>");
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let previous;
+    let network_name = if network_name == "-" {
+        previous = match resolve_previous() {
+            Ok(previous) => previous,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        previous.as_str()
+    } else {
+        network_name
+    };
 
-    let Some(network) = find_network(network_name) else {
-        eprintln!("Unknown network: {}", network_name);
-        eprintln!("Run 'stargate list' to see available networks.");
-        std::process::exit(1);
+    let found = if options.fuzzy {
+        find_network_fuzzy(network_name)
+    } else {
+        find_network(network_name).ok_or_else(|| unknown_network_message(network_name))
+    };
+
+    let network = match found {
+        Ok(network) => network,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Run 'stargate list' to see available networks.");
+            std::process::exit(1);
+        }
     };
 
+    activate(&config, network, options);
+}
+
+pub fn print_protected_banner(network: &Network) {
+    let banner = format!(
+        "!!! {} ({}) is a PROTECTED network: transactions are real !!!",
+        network.name.to_uppercase(),
+        network.chain_id
+    );
+
+    if io::stderr().is_terminal() {
+        eprintln!("{}", banner.red().bold());
+    } else {
+        eprintln!("{}", banner);
+    }
+}
+
+/// Asks on stderr before acting on a protected network, e.g. `action` "switch to".
+/// Without a terminal there is nobody to ask, so `--yes` is required.
+pub fn confirm(network: &Network, action: &str) -> Result<bool, String> {
+    if !io::stdin().is_terminal() {
+        return Err(format!(
+            "{} is protected. Pass --yes to {} it non-interactively.",
+            network.name, action
+        ));
+    }
+
+    let mut prompt = format!("{} {}?", action, network.name);
+    prompt[..1].make_ascii_uppercase();
+    eprint!("{} [y/N] ", prompt);
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|e| format!("Failed to read confirmation: {}", e))?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn activate(config: &Config, network: &Network, options: SwitchOptions) {
     // For non-anvil networks, require API key
-    if network.alchemy_subdomain.is_some() && config.api_key.is_none() {
-        eprintln!("No API key configured. Run 'stargate config set api-key <your-key>' first.");
-        std::process::exit(1);
+    let api_key = match config.api_key_for(network) {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let exports = NetworkExports::from_network(network, &api_key)
+        .with_settings(network, config)
+        .with_shell_state(|name| env::var(name).ok());
+
+    if exports.protected {
+        // `init` switches with --silent --yes in every new shell
+        if !options.silent {
+            print_protected_banner(network);
+        }
+
+        if !options.yes {
+            match confirm(network, "switch to") {
+                Ok(true) => {}
+                Ok(false) => {
+                    eprintln!("Aborted. Staying on the current network.");
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 
-    let api_key = config.api_key.as_deref().unwrap_or("");
-    let exports = NetworkExports::from_network(network, api_key);
+    let current = env::var("STARGATE_NETWORK").ok().filter(|n| !n.is_empty());
+
+    // Must be printed before the variables are overwritten by the eval
+    for name in RESTORED_VARS {
+        if env::var_os(original_var(name)).is_none()
+            && let Some(original) =
+                original_export(name, current.as_deref(), env::var(name).ok().as_deref())
+        {
+            println!("{}", original);
+        }
+    }
 
     // Output export statements for shell to eval
     println!("{}", exports.to_shell_exports());
 
+    if let Some(previous) = previous_network_export(current.as_deref(), network.name) {
+        println!("{}", previous);
+    }
+
+    if !options.no_history
+        && current.as_deref() != Some(network.name)
+        && let Err(e) = history::record(network.name)
+    {
+        eprintln!("Warning: {}", e);
+    }
+
     // User-friendly message (stderr, not captured by eval)
-    if !silent {
+    if !options.silent {
         eprintln!("Moved to {} ({})", network.name, network.chain_id);
     }
 }
@@ -114,6 +431,165 @@ mod tests {
         assert!(exports.rpc_url.contains("my-api-key"));
     }
 
+    // ==================== Protected network tests ====================
+
+    #[test]
+    fn mainnet_is_protected_by_default() {
+        let mainnet = find_network("mainnet").unwrap();
+        let exports =
+            NetworkExports::from_network(mainnet, "key").with_settings(mainnet, &Config::default());
+
+        assert!(exports.protected);
+        assert!(
+            exports
+                .to_shell_exports()
+                .contains("export STARGATE_PROTECTED=\"1\"")
+        );
+    }
+
+    #[test]
+    fn unprotected_networks_unset_protected_flag() {
+        let base = find_network("base").unwrap();
+        let exports =
+            NetworkExports::from_network(base, "key").with_settings(base, &Config::default());
+
+        assert!(!exports.protected);
+        assert!(
+            exports
+                .to_shell_exports()
+                .contains("unset STARGATE_PROTECTED")
+        );
+    }
+
+    #[test]
+    fn protection_follows_config() {
+        let config: Config = toml::from_str(
+            "[networks.mainnet]\nprotected = false\n[networks.base]\nprotected = true",
+        )
+        .unwrap();
+
+        let mainnet = find_network("mainnet").unwrap();
+        let base = find_network("base").unwrap();
+
+        assert!(
+            !NetworkExports::from_network(mainnet, "k")
+                .with_settings(mainnet, &config)
+                .protected
+        );
+        assert!(
+            NetworkExports::from_network(base, "k")
+                .with_settings(base, &config)
+                .protected
+        );
+    }
+
+    // ==================== shell_export() tests ====================
+
+    #[test]
+    fn shell_export_quotes_plain_values() {
+        assert_eq!(
+            shell_export("ETH_RPC_URL", "https://example.com/rpc"),
+            "export ETH_RPC_URL=\"https://example.com/rpc\""
+        );
+    }
+
+    #[test]
+    fn shell_export_escapes_expansions() {
+        assert_eq!(
+            shell_export("X", r#"a"b$c`d\e"#),
+            r#"export X="a\"b\$c\`d\\e""#
+        );
+    }
+
+    // ==================== previous_network_export() tests ====================
+
+    #[test]
+    fn exports_previous_network_when_leaving_one() {
+        assert_eq!(
+            previous_network_export(Some("mainnet"), "base"),
+            Some("export STARGATE_PREVIOUS_NETWORK=\"mainnet\"".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_previous_network_when_switching_to_same_network() {
+        assert_eq!(previous_network_export(Some("base"), "base"), None);
+    }
+
+    #[test]
+    fn no_previous_network_on_first_switch() {
+        assert_eq!(previous_network_export(None, "base"), None);
+        assert_eq!(previous_network_export(Some(""), "base"), None);
+    }
+
+    // ==================== original_export() tests ====================
+
+    #[test]
+    fn saves_user_rpc_url_on_first_switch() {
+        assert_eq!(
+            original_export("ETH_RPC_URL", None, Some("https://my-node.example.com")),
+            Some("export STARGATE_ORIG_ETH_RPC_URL=\"https://my-node.example.com\"".to_string())
+        );
+    }
+
+    #[test]
+    fn does_not_save_rpc_url_set_by_stargate() {
+        assert_eq!(
+            original_export(
+                "ETH_RPC_URL",
+                Some("mainnet"),
+                Some("https://eth-mainnet.g.alchemy.com/v2/k")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn nothing_to_save_without_rpc_url() {
+        assert_eq!(original_export("ETH_RPC_URL", None, None), None);
+        assert_eq!(original_export("ETH_FROM", None, Some("")), None);
+    }
+
+    #[test]
+    fn users_sender_does_not_leak_onto_networks_without_one() {
+        let config: Config = toml::from_str(
+            r#"
+[networks.1]
+from = "0x00000000000000000000000000000000000000aa"
+"#,
+        )
+        .unwrap();
+        let env = |name: &str| match name {
+            "ETH_FROM" => Some("0xmine".to_string()),
+            "ETH_KEYSTORE_ACCOUNT" => Some("mine".to_string()),
+            _ => None,
+        };
+
+        let base = find_network("base").unwrap();
+        let shell = NetworkExports::from_network(base, "k")
+            .with_settings(base, &config)
+            .with_shell_state(env)
+            .to_shell_exports();
+        assert!(shell.contains("unset ETH_FROM"), "{}", shell);
+        assert!(shell.contains("unset STARGATE_ACCOUNT"));
+        assert!(shell.contains("unset ETH_KEYSTORE_ACCOUNT"));
+
+        let mainnet = find_network("mainnet").unwrap();
+        let shell = NetworkExports::from_network(mainnet, "k")
+            .with_settings(mainnet, &config)
+            .with_shell_state(env)
+            .to_shell_exports();
+        assert!(shell.contains("export ETH_FROM=\"0x00000000000000000000000000000000000000aa\""));
+    }
+
+    #[test]
+    fn invalid_sender_is_not_exported() {
+        let config: Config = toml::from_str("[networks.base]\nfrom = \"deployer\"\n").unwrap();
+        let base = find_network("base").unwrap();
+        let exports = NetworkExports::from_network(base, "k").with_settings(base, &config);
+        assert_eq!(exports.sender, None);
+    }
+
     // ==================== NetworkExports::to_shell_exports() tests ====================
 
     #[test]
@@ -123,6 +599,12 @@ mod tests {
             network_name: "testnet".to_string(),
             chain_id: 123,
             explorer_url: Some("https://explorer.example.com".to_string()),
+            protected: false,
+            profile: None,
+            sender: None,
+            account: None,
+            addresses: Vec::new(),
+            stale_addresses: Vec::new(),
         };
 
         let shell = exports.to_shell_exports();
@@ -140,12 +622,91 @@ mod tests {
             network_name: "anvil".to_string(),
             chain_id: 31337,
             explorer_url: None,
+            protected: false,
+            profile: None,
+            sender: None,
+            account: None,
+            addresses: Vec::new(),
+            stale_addresses: Vec::new(),
         };
 
         let shell = exports.to_shell_exports();
 
         assert!(shell.contains("unset BLOCK_EXPLORER"));
         assert!(!shell.contains("export BLOCK_EXPLORER"));
+        assert!(shell.contains("unset STARGATE_PROFILE"));
+    }
+
+    #[test]
+    fn exports_sender_and_account_per_network() {
+        let config: Config = toml::from_str(
+            r#"
+[networks.base]
+account = "deployer"
+
+[networks.1]
+from = "0x00000000000000000000000000000000000000aa"
+"#,
+        )
+        .unwrap();
+
+        let base = find_network("base").unwrap();
+        let shell = NetworkExports::from_network(base, "k")
+            .with_settings(base, &config)
+            .to_shell_exports();
+        assert!(shell.contains("export STARGATE_ACCOUNT=\"deployer\""));
+        assert!(shell.contains("export ETH_KEYSTORE_ACCOUNT=\"deployer\""));
+        assert!(shell.contains("unset ETH_FROM"));
+
+        let mainnet = find_network("mainnet").unwrap();
+        let shell = NetworkExports::from_network(mainnet, "k")
+            .with_settings(mainnet, &config)
+            .to_shell_exports();
+        assert!(shell.contains("export ETH_FROM=\"0x00000000000000000000000000000000000000aa\""));
+        assert!(shell.contains("unset STARGATE_ACCOUNT"));
+        assert!(shell.contains("unset ETH_KEYSTORE_ACCOUNT"));
+    }
+
+    #[test]
+    fn exports_address_labels_after_network_vars() {
+        let network = find_network("anvil").unwrap();
+        let mut exports = NetworkExports::from_network(network, "");
+        exports.addresses = vec![("ADDR_SAFE".to_string(), "0xaa".to_string())];
+        let exports = exports.with_shell_state(|name| {
+            (name == TRACKING_VAR).then(|| "ADDR_SAFE ADDR_USDC".to_string())
+        });
+
+        let shell = exports.to_shell_exports();
+        assert!(shell.ends_with(
+            "export STARGATE_ADDR_VARS=\"ADDR_SAFE\"\nexport ADDR_SAFE=\"0xaa\"\nunset ADDR_USDC"
+        ));
+    }
+
+    #[test]
+    fn unsets_tracked_labels_when_exports_are_disabled() {
+        let network = find_network("anvil").unwrap();
+        let shell = NetworkExports::from_network(network, "")
+            .with_settings(network, &Config::default())
+            .with_shell_state(|name| (name == TRACKING_VAR).then(|| "ADDR_OLD".to_string()))
+            .to_shell_exports();
+        assert!(shell.ends_with("unset STARGATE_ADDR_VARS\nunset ADDR_OLD"));
+    }
+
+    #[test]
+    fn exports_selected_profile() {
+        let config = Config {
+            profiles: [("staging".to_string(), Default::default())].into(),
+            profile: Some("staging".to_string()),
+            ..Default::default()
+        };
+        let network = find_network("anvil").unwrap();
+        let exports = NetworkExports::from_network(network, "").with_settings(network, &config);
+
+        assert!(
+            exports
+                .to_shell_exports()
+                .contains("export STARGATE_PROFILE=\"staging\"")
+        );
     }
 
     #[test]
@@ -0,0 +1,88 @@
+use crate::commands::switch::shell_export;
+use crate::config::Config;
+use crate::vault::{SESSION_TTL_SECS, SESSION_VAR, SessionKey, Vault, end_session, start_session};
+
+fn prompt_password(prompt: &str) -> Result<String, String> {
+    rpassword::prompt_password(prompt).map_err(|e| format!("Failed to read password: {}", e))
+}
+
+/// Asks for a new vault password twice.
+fn prompt_new_password() -> Result<String, String> {
+    let password = prompt_password("New vault password: ")?;
+    if password.is_empty() {
+        return Err("Vault password cannot be empty.".to_string());
+    }
+    if prompt_password("Repeat password: ")? != password {
+        return Err("Passwords do not match.".to_string());
+    }
+    Ok(password)
+}
+
+/// Opens the existing vault with a prompted password, or creates a new one.
+fn open_or_create() -> Result<(Vault, SessionKey), String> {
+    match Vault::load()? {
+        Some(vault) => {
+            let key = vault.derive_key(&prompt_password("Vault password: ")?)?;
+            vault.open(&key)?;
+            Ok((vault, key))
+        }
+        None => Vault::create(&prompt_new_password()?),
+    }
+}
+
+fn encrypt_api_key(api_key: String) -> Result<(), String> {
+    let (mut vault, key) = open_or_create()?;
+    let mut secrets = vault.open(&key)?;
+    secrets.api_key = Some(api_key);
+    vault.seal(&key, &secrets)?;
+    vault.save()?;
+
+    // The plaintext copy would defeat the point
+    let mut config = Config::load().map_err(|e| e.to_string())?;
+    if config.api_key.take().is_some() {
+        config.save()?;
+        eprintln!("Removed the plaintext api_key from config.toml.");
+    }
+    Ok(())
+}
+
+/// `config set api-key --encrypt`
+pub fn set_api_key(api_key: String) {
+    if let Err(e) = encrypt_api_key(api_key) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    println!("API key encrypted successfully.");
+    println!("Run 'sg unlock' once per shell to use it.");
+}
+
+fn unlock_session() -> Result<String, String> {
+    let vault = Vault::load()?
+        .ok_or("No encrypted API key. Run 'stargate config set api-key --encrypt' first.")?;
+    let key = vault.derive_key(&prompt_password("Vault password: ")?)?;
+    vault.open(&key)?;
+    start_session(&key)
+}
+
+pub fn unlock() {
+    match unlock_session() {
+        Ok(token) => {
+            println!("{}", shell_export(SESSION_VAR, &token));
+            eprintln!(
+                "Vault unlocked for this shell for {} hours. Switch again to use the key.",
+                SESSION_TTL_SECS / 3600
+            );
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn lock() {
+    end_session();
+    println!("unset {}", SESSION_VAR);
+    eprintln!("Vault locked.");
+}
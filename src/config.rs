@@ -1,34 +1,369 @@
-use crate::networks::find_network;
+use crate::networks::{
+    DEFAULT_PROTECTED, LOCAL_RPC_URL, Network, all_networks, find_network, is_address,
+    unknown_network_message,
+};
+use crate::paths;
+use crate::vault::{self, Vault};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Top-level keys `Config` understands; anything else is reported by `config doctor`.
+pub const KEYS: &[&str] = &[
+    "version",
+    "api_key",
+    "api_key_env",
+    "api_key_command",
+    "default_network",
+    "provider",
+    "networks",
+    "groups",
+    "export_addresses",
+    "active_profile",
+    "profiles",
+];
+
+/// Keys understood inside `[networks.<name>]`.
+pub const NETWORK_KEYS: &[&str] = &["tags", "protected", "color", "from", "account"];
+
+/// Keys understood inside `[profiles.<name>]`.
+pub const PROFILE_KEYS: &[&str] = &[
+    "api_key",
+    "api_key_env",
+    "api_key_command",
+    "default_network",
+    "provider",
+];
+
+/// Profile chosen with `--profile`, which beats `$STARGATE_PROFILE` and `active_profile`.
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Selects a profile for the rest of this process (the global `--profile` flag).
+pub fn select_profile(name: String) {
+    let _ = PROFILE_OVERRIDE.set(name);
+}
+
+/// `STARGATE_*` variables that override a config key, e.g. in containers where
+/// `config.toml` can't be written. Merged by `load`, never saved. `active_profile`
+/// has `$STARGATE_PROFILE`; tables such as `[networks]` come from `$STARGATE_CONFIG`.
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("STARGATE_ALCHEMY_API_KEY", "api_key"),
+    ("STARGATE_API_KEY_ENV", "api_key_env"),
+    ("STARGATE_API_KEY_COMMAND", "api_key_command"),
+    ("STARGATE_DEFAULT_NETWORK", "default_network"),
+    ("STARGATE_PROVIDER", "provider"),
+    ("STARGATE_EXPORT_ADDRESSES", "export_addresses"),
+];
+
+/// Key settings; an override of any of them replaces all of them.
+const KEY_SOURCE_KEYS: &[&str] = &["api_key", "api_key_env", "api_key_command"];
+
+/// A config value taken from the environment instead of `config.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvOverride {
+    pub var: &'static str,
+    pub key: &'static str,
+    pub value: String,
+}
+
+/// An override's text as a config value: `true`/`false` are booleans.
+fn env_value(value: &str) -> toml::Value {
+    match value {
+        "true" => toml::Value::Boolean(true),
+        "false" => toml::Value::Boolean(false),
+        _ => toml::Value::String(value.to_string()),
+    }
+}
+
+/// Each of the `ENV_OVERRIDES` that is set (via `var`) to a non-empty value, checked
+/// against its key, e.g. `true` or `false` for `export_addresses`.
+fn checked_env_overrides(
+    var: impl Fn(&str) -> Option<String>,
+) -> impl Iterator<Item = Result<EnvOverride, ConfigError>> {
+    ENV_OVERRIDES.iter().filter_map(move |&(name, key)| {
+        let value = var(name).filter(|v| !v.trim().is_empty())?;
+        let value = value.trim().to_string();
+        let table = toml::Table::from_iter([(key.to_string(), env_value(&value))]);
+        if let Err(e) = toml::Value::Table(table).try_into::<Config>() {
+            return Some(Err(ConfigError::Override {
+                var: name,
+                value,
+                message: e.message().trim().to_string(),
+            }));
+        }
+        Some(Ok(EnvOverride {
+            var: name,
+            key,
+            value,
+        }))
+    })
+}
+
+fn env_overrides(var: impl Fn(&str) -> Option<String>) -> Result<Vec<EnvOverride>, ConfigError> {
+    checked_env_overrides(var).collect()
+}
+
+/// Every override `Config::load` would stop at, for `config doctor`.
+pub fn invalid_env_overrides(var: impl Fn(&str) -> Option<String>) -> Vec<ConfigError> {
+    checked_env_overrides(var).filter_map(Result::err).collect()
+}
+
+/// Layout of `config.toml` written by this release; see `MIGRATIONS`.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`. Files
+/// without a `version` key are version 0. Append here when the layout changes.
+const MIGRATIONS: &[fn(&mut toml_edit::DocumentMut)] = &[migrate_v0_to_v1];
+
+/// Version 0 had no `version` key; its fields are all still valid in version 1.
+fn migrate_v0_to_v1(_doc: &mut toml_edit::DocumentMut) {}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    /// Layout version, maintained by `migrate`.
+    #[serde(default)]
+    pub version: u32,
     pub api_key: Option<String>,
+    /// Environment variable to read the API key from, e.g. `ALCHEMY_KEY`.
+    pub api_key_env: Option<String>,
+    /// Shell command printing the API key, e.g. `pass show alchemy`.
+    pub api_key_command: Option<String>,
     pub default_network: Option<String>,
+    pub provider: Option<Provider>,
+    /// Per-network settings, keyed by network name, alias or chain ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, NetworkSettings>,
+    /// Named sets of networks, e.g. `deployments = ["mainnet", "base"]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Export address book labels as `ADDR_<LABEL>` on switch.
+    pub export_addresses: Option<bool>,
+    /// Profile used when neither `--profile` nor `$STARGATE_PROFILE` is given.
+    pub active_profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Name of the profile selected for this run, resolved by `load`.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Values from `ENV_OVERRIDES`, which win over the file and the profile.
+    #[serde(skip)]
+    pub overrides: Vec<EnvOverride>,
+}
+
+/// Where networks get their RPC endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// Alchemy for the networks it serves, a network's own RPC otherwise
+    #[default]
+    Alchemy,
+    /// Every network uses the local node, e.g. anvil forks in CI
+    Local,
+}
+
+/// Separate credentials and defaults, e.g. `[profiles.staging]` for a staging Alchemy app.
+/// Fields left unset fall back to the top-level ones.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Profile {
+    pub api_key: Option<String>,
+    pub api_key_env: Option<String>,
+    pub api_key_command: Option<String>,
+    pub default_network: Option<String>,
+    pub provider: Option<Provider>,
+}
+
+impl Profile {
+    fn has_key_source(&self) -> bool {
+        self.api_key.is_some() || self.api_key_env.is_some() || self.api_key_command.is_some()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            api_key: None,
+            api_key_env: None,
+            api_key_command: None,
+            default_network: None,
+            provider: None,
+            networks: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            export_addresses: None,
+            active_profile: None,
+            profiles: BTreeMap::new(),
+            profile: None,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// Why `config.toml` could not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        /// 1-based position of the error, when toml reports one
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    UnknownProfile {
+        name: String,
+        known: Vec<String>,
+    },
+    /// A `STARGATE_*` override with a value its key can't take
+    Override {
+        var: &'static str,
+        value: String,
+        message: String,
+    },
+    /// Written by a newer stargate
+    Migrate {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl ConfigError {
+    fn parse(path: &Path, content: &str, error: &toml::de::Error) -> Self {
+        let position = error.span().map(|span| line_col(content, span.start));
+        Self::Parse {
+            path: path.to_path_buf(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            message: error.message().trim().replace('\n', "; "),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read { path, source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            }
+            Self::Parse {
+                path,
+                line: Some(line),
+                column: Some(column),
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            Self::UnknownProfile { name, known } if known.is_empty() => write!(
+                f,
+                "Unknown profile '{}'. No profiles are defined in config.",
+                name
+            ),
+            Self::UnknownProfile { name, known } => write!(
+                f,
+                "Unknown profile '{}'. Known profiles: {}",
+                name,
+                known.join(", ")
+            ),
+            Self::Override {
+                var,
+                value,
+                message,
+            } => write!(f, "${}={:?}: {}", var, value, message),
+            Self::Parse { path, message, .. } | Self::Migrate { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset.
+fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct NetworkSettings {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Require confirmation before switching; defaults to true for mainnet.
+    pub protected: Option<bool>,
+    /// Color of the `prompt` segment (red, green, yellow, blue, magenta, cyan, dim, ...).
+    pub color: Option<String>,
+    /// Default sender address, exported as `ETH_FROM`.
+    pub from: Option<String>,
+    /// Foundry keystore account name (`cast wallet list`), exported as `ETH_KEYSTORE_ACCOUNT`.
+    pub account: Option<String>,
 }
 
 impl Config {
+    /// See `paths` for how `$STARGATE_CONFIG`, `$STARGATE_HOME` and XDG apply.
     pub fn path() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".stargate").join("config.toml"))
+        paths::config_file()
     }
 
-    pub fn load() -> Self {
+    /// Reads `config.toml` (a missing file yields the defaults) and applies the
+    /// `STARGATE_*` overrides from the environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = Self::load_file()?;
+        config.overrides = env_overrides(|name| env::var(name).ok())?;
+        Ok(config)
+    }
+
+    fn load_file() -> Result<Self, ConfigError> {
         let Some(path) = Self::path() else {
-            return Self::default();
+            return Ok(Self::default());
         };
 
         if !path.exists() {
-            return Self::default();
+            return Ok(Self::default());
         }
 
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|content| toml::from_str(&content).ok())
-            .unwrap_or_default()
+        let mut content = fs::read_to_string(&path).map_err(|source| ConfigError::Read {
+            path: path.clone(),
+            source,
+        })?;
+
+        // Upgraded in memory only: loading must work on read-only files, and the
+        // upgrade is written out by the next `save`
+        let migrated = migrate(&content).map_err(|message| ConfigError::Migrate {
+            path: path.clone(),
+            message,
+        })?;
+        if let Some((_, migrated)) = migrated {
+            content = migrated;
+        }
+
+        let mut config = Self::parse(&path, &content)?;
+        config.select_profile(|name| env::var(name).ok())?;
+
+        if config.stores_plaintext_key() && is_readable_by_others(&path) {
+            eprintln!(
+                "Warning: {} contains an API key and is readable by other users. Run 'chmod 600 {}'.",
+                path.display(),
+                path.display()
+            );
+        }
+
+        Ok(config)
     }
 
+    pub fn parse(path: &Path, content: &str) -> Result<Self, ConfigError> {
+        toml::from_str(content).map_err(|e| ConfigError::parse(path, content, &e))
+    }
+
+    /// Writes the config, editing the existing file in place so comments, ordering
+    /// and unknown keys survive. The previous version is kept as `config.toml.bak`,
+    /// and a file from an older layout is upgraded first, keeping it as `config.toml.v<N>.bak`.
     pub fn save(&self) -> Result<(), String> {
         let path = Self::path().ok_or("Could not determine home directory")?;
 
@@ -37,36 +372,685 @@ impl Config {
                 .map_err(|e| format!("Failed to create config directory: {}", e))?;
         }
 
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
-        fs::write(&path, content).map_err(|e| format!("Failed to write config: {}", e))?;
+        let existing = match fs::read_to_string(&path) {
+            Ok(existing) => Some(existing),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+
+        let mut upgraded_from = None;
+        let content = match &existing {
+            Some(existing) => {
+                let migrated = migrate(existing)?.map(|(from, migrated)| {
+                    upgraded_from = Some(from);
+                    migrated
+                });
+                let current = migrated.as_deref().unwrap_or(existing);
+                // Never replace a file the user still has to fix by hand
+                let before = Self::parse(&path, current).map_err(|e| {
+                    format!(
+                        "Refusing to overwrite unparseable config ({}). Run 'stargate config doctor' to repair it.",
+                        e
+                    )
+                })?;
+                update_document(current, &before, self)?
+            }
+            None => toml::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize config: {}", e))?,
+        };
+
+        if existing.as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+
+        if let (Some(from), Some(existing)) = (upgraded_from, &existing) {
+            // Keep the original around in case a migration gets something wrong
+            let backup = path.with_extension(format!("toml.v{}.bak", from));
+            write_private(&backup, existing)
+                .map_err(|e| format!("Failed to back up old config: {}", e))?;
+        }
+
+        replace_config_file(&path, existing.as_deref(), &content)
+    }
+
+    /// Whether any API key source is configured, without running `api_key_command`.
+    pub fn has_api_key(&self) -> bool {
+        let (api_key, api_key_env, api_key_command) = self.key_sources();
+        api_key.is_some() || api_key_env.is_some() || api_key_command.is_some() || Vault::exists()
+    }
+
+    /// Resolves `--profile`, `$STARGATE_PROFILE` (read via `var`) and `active_profile`,
+    /// in that order.
+    fn select_profile(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let name = PROFILE_OVERRIDE
+            .get()
+            .cloned()
+            .or_else(|| var("STARGATE_PROFILE").filter(|p| !p.is_empty()))
+            .or_else(|| self.active_profile.clone());
+
+        if let Some(name) = &name
+            && !self.profiles.contains_key(name)
+        {
+            return Err(ConfigError::UnknownProfile {
+                name: name.clone(),
+                known: self.profiles.keys().cloned().collect(),
+            });
+        }
+
+        self.profile = name;
+        Ok(())
+    }
+
+    /// The profile selected for this run.
+    pub fn selected_profile(&self) -> Option<&Profile> {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    fn stores_plaintext_key(&self) -> bool {
+        self.api_key.is_some() || self.profiles.values().any(|p| p.api_key.is_some())
+    }
+
+    /// The value of `key` set through the environment, if any.
+    pub fn env_override(&self, key: &str) -> Option<&EnvOverride> {
+        let key = split_key(key).ok()?.join(".");
+        self.overrides.iter().find(|o| o.key == key)
+    }
+
+    fn overridden(&self, key: &str) -> Option<&str> {
+        self.env_override(key).map(|o| o.value.as_str())
+    }
+
+    /// A typed override; `load` has already checked that it converts.
+    fn overridden_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.overridden(key)
+            .and_then(|value| env_value(value).try_into().ok())
+    }
+
+    /// The variable hiding the file's value at `key` without replacing it: a key
+    /// setting override, which hides every key setting, profiles' included.
+    pub fn shadowed_by(&self, key: &str) -> Option<&'static str> {
+        let path = split_key(key).ok()?;
+        let setting = match path.as_slice() {
+            [setting] => setting,
+            [profiles, _, setting] if profiles == "profiles" => setting,
+            _ => return None,
+        };
+        if !KEY_SOURCE_KEYS.contains(&setting.as_str()) {
+            return None;
+        }
+        self.overrides
+            .iter()
+            .find(|o| KEY_SOURCE_KEYS.contains(&o.key) && o.key != key)
+            .map(|o| o.var)
+    }
+
+    /// Key settings from the environment if any are set, else the selected
+    /// profile's if it has any, otherwise the top-level ones.
+    fn key_sources(&self) -> (Option<&str>, Option<&str>, Option<&str>) {
+        let env = (
+            self.overridden("api_key"),
+            self.overridden("api_key_env"),
+            self.overridden("api_key_command"),
+        );
+        if env.0.is_some() || env.1.is_some() || env.2.is_some() {
+            return env;
+        }
+
+        match self.selected_profile().filter(|p| p.has_key_source()) {
+            Some(p) => (
+                p.api_key.as_deref(),
+                p.api_key_env.as_deref(),
+                p.api_key_command.as_deref(),
+            ),
+            None => (
+                self.api_key.as_deref(),
+                self.api_key_env.as_deref(),
+                self.api_key_command.as_deref(),
+            ),
+        }
+    }
+
+    /// The API key from `api_key`, the variable named by `api_key_env`, the
+    /// output of `api_key_command`, or the encrypted vault, in that order.
+    /// Overrides from the environment, then a selected profile's key settings,
+    /// replace the top-level ones.
+    pub fn resolve_api_key(&self) -> Result<Option<String>, String> {
+        let (api_key, api_key_env, api_key_command) = self.key_sources();
+
+        if let Some(key) = api_key {
+            return Ok(Some(key.to_string()));
+        }
+
+        if let Some(var) = api_key_env {
+            return match env::var(var) {
+                Ok(key) if !key.trim().is_empty() => Ok(Some(key.trim().to_string())),
+                _ => Err(format!(
+                    "api_key_env is set to '{}', but ${} is empty or not set.",
+                    var, var
+                )),
+            };
+        }
+
+        match api_key_command {
+            Some(command) => run_key_command(command).map(Some),
+            None => vault::api_key(),
+        }
+    }
+
+    /// `$STARGATE_PROVIDER`, else the selected profile's `provider`, else the
+    /// top-level one, else Alchemy.
+    pub fn provider(&self) -> Provider {
+        self.overridden_as("provider")
+            .or(self.selected_profile().and_then(|p| p.provider))
+            .or(self.provider)
+            .unwrap_or_default()
+    }
+
+    /// Whether reaching `network` takes the Alchemy API key.
+    pub fn needs_api_key(&self, network: &Network) -> bool {
+        network.alchemy_subdomain.is_some() && self.provider() == Provider::Alchemy
+    }
+
+    /// RPC endpoint for `network` with the configured provider.
+    pub fn rpc_url(&self, network: &Network, api_key: &str) -> String {
+        match self.provider() {
+            Provider::Alchemy => network.rpc_url(api_key),
+            Provider::Local => LOCAL_RPC_URL.to_string(),
+        }
+    }
+
+    /// Where `network`'s RPC endpoint comes from: "alchemy", "custom" or "local".
+    pub fn provider_for(&self, network: &Network) -> &'static str {
+        match self.provider() {
+            Provider::Alchemy => network.provider(),
+            Provider::Local => "local",
+        }
+    }
+
+    /// The API key needed to reach `network`; empty for networks that don't use one.
+    pub fn api_key_for(&self, network: &Network) -> Result<String, String> {
+        if !self.needs_api_key(network) {
+            return Ok(String::new());
+        }
+
+        self.resolve_api_key()?.ok_or_else(|| {
+            "No API key configured. Run 'stargate config set api-key <your-key>' first.".to_string()
+        })
+    }
+
+    /// Value at a dotted key path such as `networks.base.color`.
+    pub fn get_key(&self, key: &str) -> Result<Option<toml::Value>, String> {
+        let path = split_key(key)?;
+        Ok(lookup(&to_table(self)?, &path).cloned())
+    }
+
+    /// Sets a dotted key path. The result must deserialize back into a `Config`
+    /// that still has the value, so typos and wrong types are rejected.
+    pub fn set_key(&mut self, key: &str, value: toml::Value) -> Result<(), String> {
+        let path = split_key(key)?;
+        let mut table = to_table(self)?;
+        insert_path(&mut table, &path, value.clone())
+            .map_err(|e| format!("Cannot set '{}': {}", key, e))?;
 
+        let updated: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("Invalid value for '{}': {}", key, e))?;
+
+        if let [networks, _, field] = path.as_slice()
+            && networks == "networks"
+            && field == "from"
+            && let toml::Value::String(from) = &value
+            && !is_address(from)
+        {
+            return Err(format!(
+                "'{}' is not an address (expected 0x followed by 40 hex digits).",
+                from
+            ));
+        }
+
+        let stored = lookup(&to_table(&updated)?, &path).cloned();
+        let is_empty = match &value {
+            toml::Value::Array(a) => a.is_empty(),
+            toml::Value::Table(t) => t.is_empty(),
+            _ => false,
+        };
+        if stored.as_ref() != Some(&value) && !(stored.is_none() && is_empty) {
+            return Err(format!("Unknown config key '{}'", key));
+        }
+
+        // The selected profile and overrides aren't part of the file
+        *self = Config {
+            profile: self.profile.take(),
+            overrides: std::mem::take(&mut self.overrides),
+            ..updated
+        };
         Ok(())
     }
 
-    pub fn set_api_key(&mut self, key: String) -> Result<(), String> {
-        self.api_key = Some(key);
-        self.save()
+    /// Removes a dotted key path, dropping tables it leaves empty.
+    pub fn unset_key(&mut self, key: &str) -> Result<(), String> {
+        let path = split_key(key)?;
+        let mut table = to_table(self)?;
+        if !remove_path(&mut table, &path) {
+            return Err(format!("'{}' is not set", key));
+        }
+
+        let updated: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("Cannot unset '{}': {}", key, e))?;
+        *self = Config {
+            profile: self.profile.take(),
+            overrides: std::mem::take(&mut self.overrides),
+            ..updated
+        };
+        Ok(())
+    }
+
+    /// Every set value as `(dotted key, value)`, sorted by key.
+    pub fn entries(&self) -> Result<Vec<(String, toml::Value)>, String> {
+        let mut entries = Vec::new();
+        flatten("", &to_table(self)?, &mut entries);
+        Ok(entries)
+    }
+
+    /// Sets `key` in the selected profile if there is one, else at the top level.
+    pub fn set_api_key(&mut self, key: String) {
+        match self.profile.as_ref().and_then(|p| self.profiles.get_mut(p)) {
+            Some(profile) => profile.api_key = Some(key),
+            None => self.api_key = Some(key),
+        }
+    }
+
+    /// `$STARGATE_DEFAULT_NETWORK`, else the selected profile's default network,
+    /// else the top-level one.
+    pub fn configured_default_network(&self) -> Option<&str> {
+        self.overridden("default_network")
+            .or(self
+                .selected_profile()
+                .and_then(|p| p.default_network.as_deref()))
+            .or(self.default_network.as_deref())
     }
 
     pub fn get_default_network(&self) -> &str {
-        self.default_network.as_deref().unwrap_or("anvil")
+        self.configured_default_network().unwrap_or("anvil")
     }
 
     pub fn set_default_network(&mut self, network: String) -> Result<(), String> {
         let found_network = find_network(&network).ok_or_else(|| {
             format!(
-                "Unknown network: '{}'. Run 'stargate list' to see available networks.",
-                network
+                "{} Run 'stargate list' to see available networks.",
+                unknown_network_message(&network)
             )
         })?;
 
-        // Store canonical name, not alias
-        self.default_network = Some(found_network.name.to_string());
-        self.save()
+        // Store canonical name, not alias; with a profile selected, in that profile
+        let name = Some(found_network.name.to_string());
+        match self.profile.as_ref().and_then(|p| self.profiles.get_mut(p)) {
+            Some(profile) => profile.default_network = name,
+            None => self.default_network = name,
+        }
+        Ok(())
+    }
+
+    /// Settings entries that apply to `network` (keys may be names, aliases or chain IDs).
+    fn settings_for<'a>(&'a self, network: &Network) -> Vec<&'a NetworkSettings> {
+        self.networks
+            .iter()
+            .filter(|(key, _)| network.matches(key))
+            .map(|(_, settings)| settings)
+            .collect()
+    }
+
+    pub fn tags_for<'a>(&'a self, network: &Network) -> Vec<&'a str> {
+        let mut tags: Vec<&str> = self
+            .settings_for(network)
+            .into_iter()
+            .flat_map(|s| s.tags.iter().map(|t| t.as_str()))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn is_protected(&self, network: &Network) -> bool {
+        self.settings_for(network)
+            .into_iter()
+            .find_map(|s| s.protected)
+            .unwrap_or_else(|| DEFAULT_PROTECTED.contains(&network.name))
+    }
+
+    pub fn color_for(&self, network: &Network) -> Option<&str> {
+        self.settings_for(network)
+            .into_iter()
+            .find_map(|s| s.color.as_deref())
+    }
+
+    pub fn sender_for(&self, network: &Network) -> Option<&str> {
+        self.settings_for(network)
+            .into_iter()
+            .find_map(|s| s.from.as_deref())
+    }
+
+    pub fn account_for(&self, network: &Network) -> Option<&str> {
+        self.settings_for(network)
+            .into_iter()
+            .find_map(|s| s.account.as_deref())
+    }
+
+    pub fn exports_addresses(&self) -> bool {
+        self.overridden_as("export_addresses")
+            .or(self.export_addresses)
+            .unwrap_or(false)
+    }
+
+    pub fn networks_with_tag(&self, tag: &str) -> Vec<&'static Network> {
+        all_networks()
+            .filter(|n| self.tags_for(n).contains(&tag))
+            .collect()
+    }
+
+    /// Resolves a named group to its networks, failing on unknown groups or members.
+    pub fn group(&self, name: &str) -> Result<Vec<&'static Network>, String> {
+        let members = self.groups.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.groups.keys().map(|k| k.as_str()).collect();
+            if known.is_empty() {
+                format!(
+                    "Unknown group: '{}'. No groups are defined in config.",
+                    name
+                )
+            } else {
+                format!(
+                    "Unknown group: '{}'. Known groups: {}",
+                    name,
+                    known.join(", ")
+                )
+            }
+        })?;
+
+        members
+            .iter()
+            .map(|member| {
+                find_network(member).ok_or_else(|| {
+                    format!("Group '{}' references unknown network '{}'", name, member)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs `api_key_command` through `sh -c`. Stderr and stdin stay attached so password
+/// managers can prompt; the first line of stdout is the key.
+fn run_key_command(command: &str) -> Result<String, String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("Failed to run api_key_command: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "api_key_command '{}' failed ({}).",
+            command, output.status
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().next().map(str::trim) {
+        Some(key) if !key.is_empty() => Ok(key.to_string()),
+        _ => Err(format!("api_key_command '{}' printed nothing.", command)),
+    }
+}
+
+/// Upgrades `content` to `CONFIG_VERSION`, returning the version it started from and
+/// the new text, or `None` if it is current. Unparseable files are left for
+/// `Config::parse` to report.
+pub(crate) fn migrate(content: &str) -> Result<Option<(u32, String)>, String> {
+    let Ok(mut doc) = content.parse::<toml_edit::DocumentMut>() else {
+        return Ok(None);
+    };
+
+    let version = match doc.get("version") {
+        None => 0,
+        Some(item) => item
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or("'version' must be a non-negative integer")?,
+    };
+
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "config version {} is newer than this stargate supports ({}). Upgrade stargate.",
+            version, CONFIG_VERSION
+        ));
+    }
+    if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut doc);
+    }
+
+    doc["version"] = toml_edit::value(i64::from(CONFIG_VERSION));
+    // Put `version` first, ahead of the user's keys
+    doc.sort_values_by(|a, _, b, _| (a.get() != "version").cmp(&(b.get() != "version")));
+    Ok(Some((version, doc.to_string())))
+}
+
+/// Atomically writes a new `config.toml`, keeping `previous` as `config.toml.bak`.
+pub(crate) fn replace_config_file(
+    path: &Path,
+    previous: Option<&str>,
+    content: &str,
+) -> Result<(), String> {
+    if let Some(previous) = previous {
+        write_private(&path.with_extension("toml.bak"), previous)
+            .map_err(|e| format!("Failed to back up config: {}", e))?;
+    }
+    write_atomic(path, content).map_err(|e| format!("Failed to write config: {}", e))
+}
+
+fn to_table(config: &Config) -> Result<toml::Table, String> {
+    toml::Table::try_from(config).map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// Whether a key path names a secret that should be masked when displayed.
+pub fn is_secret_key(key: &str) -> bool {
+    key.rsplit('.').next() == Some("api_key")
+}
+
+/// Splits `networks.base.color` into segments. Top-level names may use dashes
+/// (`default-network`), matching the CLI spelling.
+fn split_key(key: &str) -> Result<Vec<String>, String> {
+    let mut path: Vec<String> = key.split('.').map(str::to_string).collect();
+    if path.iter().any(|s| s.is_empty()) {
+        return Err(format!("Invalid key '{}'", key));
+    }
+    path[0] = path[0].replace('-', "_");
+    Ok(path)
+}
+
+fn lookup<'a>(table: &'a toml::Table, path: &[String]) -> Option<&'a toml::Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for segment in parents {
+        table = table.get(segment)?.as_table()?;
+    }
+    table.get(last)
+}
+
+fn insert_path(table: &mut toml::Table, path: &[String], value: toml::Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().ok_or("empty key")?;
+    let mut table = table;
+    for segment in parents {
+        table = table
+            .entry(segment.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| format!("'{}' is not a table", segment))?;
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+fn remove_path(table: &mut toml::Table, path: &[String]) -> bool {
+    match path {
+        [] => false,
+        [last] => table.remove(last).is_some(),
+        [first, rest @ ..] => {
+            let Some(child) = table.get_mut(first).and_then(|v| v.as_table_mut()) else {
+                return false;
+            };
+            let removed = remove_path(child, rest);
+            if removed && child.is_empty() {
+                table.remove(first);
+            }
+            removed
+        }
     }
 }
 
+fn flatten(prefix: &str, table: &toml::Table, entries: &mut Vec<(String, toml::Value)>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(nested) => flatten(&path, nested, entries),
+            value => entries.push((path, value.clone())),
+        }
+    }
+}
+
+/// A formatted `toml_edit` item for `value`, as it would appear under `key`.
+fn to_item(key: &str, value: &toml::Value) -> Result<toml_edit::Item, String> {
+    let mut table = toml::Table::new();
+    table.insert(key.to_string(), value.clone());
+    let rendered =
+        toml::to_string(&table).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let mut doc: toml_edit::DocumentMut = rendered
+        .parse()
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    doc.remove(key)
+        .ok_or_else(|| format!("Failed to serialize config key '{}'", key))
+}
+
+/// Separates newly added `[table]` headers from what precedes them with a blank line.
+fn space_tables(item: &mut toml_edit::Item) {
+    if let Some(table) = item.as_table_mut() {
+        if !table.is_implicit() {
+            table.decor_mut().set_prefix("\n");
+        }
+        for (_, child) in table.iter_mut() {
+            space_tables(child);
+        }
+    }
+}
+
+/// Applies the difference between `before` and `after` to `doc`. Keys that didn't
+/// change are left untouched, as is anything `before` doesn't know about (comments,
+/// unknown keys), so only the user's edits through stargate show up in the file.
+fn merge_table(
+    doc: &mut dyn toml_edit::TableLike,
+    before: &toml::Table,
+    after: &toml::Table,
+) -> Result<(), String> {
+    for key in before.keys().filter(|k| !after.contains_key(*k)) {
+        doc.remove(key);
+    }
+
+    for (key, value) in after {
+        let old = before.get(key);
+        if old == Some(value) {
+            continue;
+        }
+
+        if let (Some(toml::Value::Table(old)), toml::Value::Table(new)) = (old, value)
+            && let Some(nested) = doc.get_mut(key).and_then(|i| i.as_table_like_mut())
+        {
+            merge_table(nested, old, new)?;
+            continue;
+        }
+
+        let mut item = to_item(key, value)?;
+        space_tables(&mut item);
+        match doc.get_mut(key) {
+            // Keep the key's position and trailing comment
+            Some(toml_edit::Item::Value(existing)) if item.is_value() => {
+                let decor = existing.decor().clone();
+                *existing = item.into_value().expect("checked is_value");
+                *existing.decor_mut() = decor;
+            }
+            _ => {
+                doc.insert(key, item);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrites `content` (which parsed as `before`) so it parses as `after`.
+fn update_document(content: &str, before: &Config, after: &Config) -> Result<String, String> {
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    merge_table(doc.as_table_mut(), &to_table(before)?, &to_table(after)?)?;
+    Ok(doc.to_string())
+}
+
+/// Writes to a temporary sibling and renames it over `path`, so a crash leaves
+/// either the old or the new file, never half of one. A symlinked `path` (e.g. from
+/// a dotfile manager) is followed, so the link survives and its target is replaced.
+pub(crate) fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let path = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(e) => return Err(e),
+    };
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    write_private(&tmp, content)?;
+    fs::rename(&tmp, &path)
+}
+
+/// Writes `content` so only the owner can read it, since the config may hold an API key.
+/// Existing files are tightened to 0600 as well.
+pub(crate) fn write_private(path: &Path, content: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+#[cfg(unix)]
+pub(crate) fn is_readable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o044 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_readable_by_others(_path: &Path) -> bool {
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +1066,7 @@ mod tests {
         let config = Config {
             api_key: Some("test-key-123".to_string()),
             default_network: None,
+            ..Default::default()
         };
         let toml_str = toml::to_string(&config).unwrap();
         assert!(toml_str.contains("api_key"));
@@ -111,9 +1096,10 @@ mod tests {
 
     #[test]
     fn config_path_ends_with_expected_segments() {
-        if let Some(path) = Config::path() {
-            let path_str = path.to_string_lossy();
-            assert!(path_str.ends_with(".stargate/config.toml"));
+        if let Some(path) = Config::path()
+            && env::var_os("STARGATE_CONFIG").is_none()
+        {
+            assert!(path.ends_with("config.toml"));
         }
         // If home dir is not available, path() returns None which is acceptable
     }
@@ -123,6 +1109,7 @@ mod tests {
         let original = Config {
             api_key: Some("roundtrip-test-key".to_string()),
             default_network: None,
+            ..Default::default()
         };
 
         let toml_str = toml::to_string(&original).unwrap();
@@ -148,6 +1135,7 @@ mod tests {
         let config = Config {
             api_key: None,
             default_network: Some("polygon".to_string()),
+            ..Default::default()
         };
         assert_eq!(config.get_default_network(), "polygon");
     }
@@ -155,17 +1143,14 @@ mod tests {
     #[test]
     fn set_default_network_accepts_valid_network_name() {
         let mut config = Config::default();
-        let _result = config.set_default_network("mainnet".to_string());
-
-        // Should succeed (or fail only due to save, not validation)
-        // We check that the network was stored correctly
+        config.set_default_network("mainnet".to_string()).unwrap();
         assert_eq!(config.default_network, Some("mainnet".to_string()));
     }
 
     #[test]
     fn set_default_network_accepts_valid_alias() {
         let mut config = Config::default();
-        let _result = config.set_default_network("arb".to_string());
+        config.set_default_network("arb".to_string()).unwrap();
 
         // Should normalize "arb" to "arbitrum"
         assert_eq!(config.default_network, Some("arbitrum".to_string()));
@@ -174,7 +1159,7 @@ mod tests {
     #[test]
     fn set_default_network_accepts_valid_chain_id() {
         let mut config = Config::default();
-        let _result = config.set_default_network("1".to_string());
+        config.set_default_network("1".to_string()).unwrap();
 
         // Should normalize "1" to "mainnet"
         assert_eq!(config.default_network, Some("mainnet".to_string()));
@@ -195,6 +1180,7 @@ mod tests {
         let config = Config {
             api_key: Some("test-key".to_string()),
             default_network: Some("polygon".to_string()),
+            ..Default::default()
         };
         let toml_str = toml::to_string(&config).unwrap();
         assert!(toml_str.contains("default_network"));
@@ -225,11 +1211,659 @@ default_network = "arbitrum"
         let mut config = Config {
             api_key: Some("existing-api-key".to_string()),
             default_network: None,
+            ..Default::default()
         };
 
-        let _result = config.set_default_network("mainnet".to_string());
+        config.set_default_network("mainnet".to_string()).unwrap();
 
         assert_eq!(config.api_key, Some("existing-api-key".to_string()));
         assert_eq!(config.default_network, Some("mainnet".to_string()));
     }
+
+    // ==================== Tags and groups tests ====================
+
+    const TAGGED: &str = r#"
+[networks.base]
+tags = ["l2", "op-stack"]
+
+[networks.op]
+tags = ["l2", "op-stack"]
+
+[networks.1]
+tags = ["prod"]
+
+[groups]
+deployments = ["mainnet", "base", "arb"]
+broken = ["mainnet", "not-a-network"]
+"#;
+
+    #[test]
+    fn config_deserializes_tags_and_groups() {
+        let config: Config = toml::from_str(TAGGED).unwrap();
+        assert_eq!(config.networks.len(), 3);
+        assert_eq!(config.groups["deployments"].len(), 3);
+    }
+
+    #[test]
+    fn tags_for_matches_settings_by_alias_and_chain_id() {
+        let config: Config = toml::from_str(TAGGED).unwrap();
+
+        let optimism = find_network("optimism").unwrap();
+        assert_eq!(config.tags_for(optimism), vec!["l2", "op-stack"]);
+
+        let mainnet = find_network("mainnet").unwrap();
+        assert_eq!(config.tags_for(mainnet), vec!["prod"]);
+
+        let polygon = find_network("polygon").unwrap();
+        assert!(config.tags_for(polygon).is_empty());
+    }
+
+    #[test]
+    fn is_protected_defaults_to_mainnet_only() {
+        let config = Config::default();
+        assert!(config.is_protected(find_network("mainnet").unwrap()));
+        assert!(!config.is_protected(find_network("base").unwrap()));
+        assert!(!config.is_protected(find_network("anvil").unwrap()));
+    }
+
+    #[test]
+    fn is_protected_can_be_overridden() {
+        let config: Config = toml::from_str(
+            "[networks.eth]\nprotected = false\n\n[networks.42161]\nprotected = true",
+        )
+        .unwrap();
+        assert!(!config.is_protected(find_network("mainnet").unwrap()));
+        assert!(config.is_protected(find_network("arbitrum").unwrap()));
+    }
+
+    #[test]
+    fn networks_with_tag_returns_all_tagged_networks() {
+        let config: Config = toml::from_str(TAGGED).unwrap();
+        let names: Vec<&str> = config
+            .networks_with_tag("op-stack")
+            .iter()
+            .map(|n| n.name)
+            .collect();
+        assert_eq!(names, vec!["optimism", "base"]);
+    }
+
+    #[test]
+    fn group_resolves_members_to_canonical_networks() {
+        let config: Config = toml::from_str(TAGGED).unwrap();
+        let names: Vec<&str> = config
+            .group("deployments")
+            .unwrap()
+            .iter()
+            .map(|n| n.name)
+            .collect();
+        assert_eq!(names, vec!["mainnet", "base", "arbitrum"]);
+    }
+
+    #[test]
+    fn group_rejects_unknown_group_and_members() {
+        let config: Config = toml::from_str(TAGGED).unwrap();
+
+        let err = config.group("nope").unwrap_err();
+        assert!(err.contains("Unknown group"));
+        assert!(err.contains("deployments"));
+
+        let err = config.group("broken").unwrap_err();
+        assert!(err.contains("not-a-network"));
+    }
+
+    #[test]
+    fn config_without_tags_serializes_without_tables() {
+        let config = Config {
+            api_key: Some("key".to_string()),
+            ..Default::default()
+        };
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(!toml_str.contains("networks"));
+        assert!(!toml_str.contains("groups"));
+    }
+
+    #[test]
+    fn resolve_api_key_prefers_plaintext_key() {
+        let config = Config {
+            api_key: Some("plain".to_string()),
+            api_key_command: Some("echo from-command".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.resolve_api_key().unwrap().as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn resolve_api_key_reads_environment_variable() {
+        // SAFETY: the variable name is unique to this test.
+        unsafe { env::set_var("STARGATE_TEST_RESOLVE_KEY", " from-env\n") };
+        let config = Config {
+            api_key_env: Some("STARGATE_TEST_RESOLVE_KEY".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve_api_key().unwrap().as_deref(),
+            Some("from-env")
+        );
+
+        let config = Config {
+            api_key_env: Some("STARGATE_TEST_UNSET_KEY".to_string()),
+            ..Default::default()
+        };
+        assert!(config.resolve_api_key().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_api_key_runs_command() {
+        let config = Config {
+            api_key_command: Some("printf 'from-command\\nsecond line'".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve_api_key().unwrap().as_deref(),
+            Some("from-command")
+        );
+
+        let config = Config {
+            api_key_command: Some("exit 3".to_string()),
+            ..Default::default()
+        };
+        assert!(config.resolve_api_key().is_err());
+    }
+
+    #[test]
+    fn api_key_for_skips_networks_without_alchemy() {
+        let config = Config::default();
+        assert_eq!(
+            config.api_key_for(find_network("anvil").unwrap()).unwrap(),
+            ""
+        );
+        assert!(
+            config
+                .api_key_for(find_network("mainnet").unwrap())
+                .is_err()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_private_creates_owner_only_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = env::temp_dir().join(format!("stargate-private-{}", std::process::id()));
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(is_readable_by_others(&path));
+
+        write_private(&path, "api_key = \"k\"").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!is_readable_by_others(&path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "api_key = \"k\"");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse_error_reports_line_and_column() {
+        let path = Path::new("/tmp/config.toml");
+        let err = Config::parse(path, "default_network = \"base\"\napi_key = oops\n").unwrap_err();
+        match &err {
+            ConfigError::Parse { line, column, .. } => {
+                assert_eq!(*line, Some(2));
+                assert_eq!(*column, Some(11));
+            }
+            other => panic!("expected parse error, got {:?}", other),
+        }
+        assert!(err.to_string().starts_with("/tmp/config.toml:2:11: "));
+    }
+
+    #[test]
+    fn line_col_counts_from_one() {
+        assert_eq!(line_col("abc", 0), (1, 1));
+        assert_eq!(line_col("ab\ncd", 4), (2, 2));
+    }
+
+    fn update(content: &str, edit: impl FnOnce(&mut Config)) -> String {
+        let before: Config = toml::from_str(content).unwrap();
+        let mut after: Config = toml::from_str(content).unwrap();
+        edit(&mut after);
+        update_document(content, &before, &after).unwrap()
+    }
+
+    #[test]
+    fn save_preserves_comments_order_and_unknown_keys() {
+        let content = r#"# my stargate config
+default_network = "base" # used by new shells
+future_option = true
+
+api_key = "old"
+"#;
+        let updated = update(content, |c| c.api_key = Some("new".to_string()));
+        assert_eq!(
+            updated,
+            r#"# my stargate config
+default_network = "base" # used by new shells
+future_option = true
+
+api_key = "new"
+"#
+        );
+    }
+
+    #[test]
+    fn save_removes_unset_keys_and_adds_new_tables() {
+        let content = "api_key = \"k\"\ndefault_network = \"base\"\n";
+        let updated = update(content, |c| {
+            c.default_network = None;
+            c.networks.insert(
+                "base".to_string(),
+                NetworkSettings {
+                    tags: vec!["l2".to_string()],
+                    ..Default::default()
+                },
+            );
+        });
+        assert_eq!(
+            updated,
+            "api_key = \"k\"\n\n[networks.base]\ntags = [\"l2\"]\n"
+        );
+    }
+
+    #[test]
+    fn save_edits_nested_tables_in_place() {
+        let content = r#"[networks.mainnet]
+# careful here
+protected = true
+color = "red"
+
+[networks.base]
+tags = ["l2"]
+"#;
+        let updated = update(content, |c| {
+            c.networks
+                .get_mut("base")
+                .unwrap()
+                .tags
+                .push("cheap".to_string());
+        });
+        assert_eq!(
+            updated,
+            r#"[networks.mainnet]
+# careful here
+protected = true
+color = "red"
+
+[networks.base]
+tags = ["l2", "cheap"]
+"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_replaces_file_without_leftovers() {
+        let path = env::temp_dir().join(format!("stargate-atomic-{}.toml", std::process::id()));
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!path.with_extension("toml.tmp").exists());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_symlinks() {
+        let dir = env::temp_dir().join(format!("stargate-symlink-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("dotfiles-config.toml");
+        let link = dir.join("config.toml");
+        fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn key_paths_get_set_and_unset() {
+        let mut config = Config::default();
+        config
+            .set_key(
+                "networks.base.color",
+                toml::Value::String("blue".to_string()),
+            )
+            .unwrap();
+        config
+            .set_key("default-network", toml::Value::String("base".to_string()))
+            .unwrap();
+
+        assert_eq!(config.default_network.as_deref(), Some("base"));
+        assert_eq!(
+            config.get_key("networks.base.color").unwrap(),
+            Some(toml::Value::String("blue".to_string()))
+        );
+
+        config.unset_key("networks.base.color").unwrap();
+        assert!(config.networks.is_empty());
+        assert!(config.unset_key("networks.base.color").is_err());
+    }
+
+    #[test]
+    fn set_key_rejects_unknown_keys_and_wrong_types() {
+        let mut config = Config::default();
+        let err = config
+            .set_key("providers.infura.key", toml::Value::String("x".to_string()))
+            .unwrap_err();
+        assert_eq!(err, "Unknown config key 'providers.infura.key'");
+        assert!(
+            config
+                .set_key(
+                    "networks.base.protected",
+                    toml::Value::String("yes".to_string())
+                )
+                .is_err()
+        );
+        assert!(config.networks.is_empty());
+    }
+
+    #[test]
+    fn entries_flatten_nested_tables() {
+        let config: Config = toml::from_str(
+            "api_key = \"k\"\n[networks.base]\ntags = [\"l2\"]\n[groups]\nprod = [\"mainnet\"]\n",
+        )
+        .unwrap();
+        let keys: Vec<String> = config
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["api_key", "groups.prod", "networks.base.tags", "version"]
+        );
+        assert!(is_secret_key("api_key"));
+        assert!(!is_secret_key("api_key_env"));
+    }
+
+    /// Every historical layout, with the key and default network it must keep.
+    const FIXTURES: &[(&str, &str, Option<&str>, &str)] = &[
+        (
+            "v0-baseline",
+            include_str!("../tests/fixtures/config/v0-baseline.toml"),
+            Some("baseline-key"),
+            "base",
+        ),
+        (
+            "v0-tags-groups",
+            include_str!("../tests/fixtures/config/v0-tags-groups.toml"),
+            Some("tagged-key"),
+            "sepolia",
+        ),
+        (
+            "v0-key-sources",
+            include_str!("../tests/fixtures/config/v0-key-sources.toml"),
+            None,
+            "arbitrum",
+        ),
+        (
+            "v1",
+            include_str!("../tests/fixtures/config/v1.toml"),
+            Some("current-key"),
+            "optimism",
+        ),
+    ];
+
+    #[test]
+    fn migrations_keep_api_key_and_default_network() {
+        for (name, content, api_key, default_network) in FIXTURES {
+            let migrated = migrate(content)
+                .unwrap()
+                .map_or(content.to_string(), |(_, m)| m);
+            let config: Config = toml::from_str(&migrated).unwrap();
+
+            assert_eq!(config.version, CONFIG_VERSION, "{}", name);
+            assert_eq!(config.api_key.as_deref(), *api_key, "{}", name);
+            assert_eq!(config.get_default_network(), *default_network, "{}", name);
+            assert!(
+                migrate(&migrated).unwrap().is_none(),
+                "{} migrated twice",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn migration_preserves_comments_and_settings() {
+        let content = include_str!("../tests/fixtures/config/v0-tags-groups.toml");
+        let (from, migrated) = migrate(content).unwrap().unwrap();
+        assert_eq!(from, 0);
+        assert!(
+            migrated.starts_with("version = 1\n# Written before config files were versioned\n")
+        );
+        assert!(migrated.contains("api_key = \"tagged-key\" # alchemy"));
+
+        let config: Config = toml::from_str(&migrated).unwrap();
+        assert!(config.is_protected(find_network("mainnet").unwrap()));
+        assert_eq!(config.group("deployments").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn migrate_rejects_newer_versions() {
+        let err = migrate("version = 99\n").unwrap_err();
+        assert!(err.contains("newer than this stargate supports"));
+        assert!(migrate("version = \"one\"\n").is_err());
+    }
+
+    #[test]
+    fn new_configs_start_at_current_version() {
+        let content = toml::to_string_pretty(&Config::default()).unwrap();
+        assert_eq!(content, format!("version = {}\n", CONFIG_VERSION));
+    }
+
+    fn profiles_config() -> Config {
+        toml::from_str(
+            r#"
+api_key = "personal"
+default_network = "sepolia"
+active_profile = "staging"
+
+[profiles.staging]
+api_key = "staging"
+default_network = "base"
+provider = "local"
+
+[profiles.prod]
+default_network = "mainnet"
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn active_profile_overrides_top_level_settings() {
+        let mut config = profiles_config();
+        config.select_profile(|_| None).unwrap();
+
+        assert_eq!(config.profile.as_deref(), Some("staging"));
+        assert_eq!(
+            config.resolve_api_key().unwrap().as_deref(),
+            Some("staging")
+        );
+        assert_eq!(config.get_default_network(), "base");
+        assert_eq!(config.provider(), Provider::Local);
+    }
+
+    #[test]
+    fn profile_without_key_sources_keeps_top_level_key() {
+        let config = Config {
+            profile: Some("prod".to_string()),
+            ..profiles_config()
+        };
+        assert_eq!(
+            config.resolve_api_key().unwrap().as_deref(),
+            Some("personal")
+        );
+        assert_eq!(config.get_default_network(), "mainnet");
+        assert_eq!(config.provider(), Provider::Alchemy);
+    }
+
+    #[test]
+    fn unknown_active_profile_is_an_error() {
+        let mut config = Config {
+            active_profile: Some("qa".to_string()),
+            ..profiles_config()
+        };
+
+        let err = config.select_profile(|_| None).unwrap_err().to_string();
+        assert_eq!(err, "Unknown profile 'qa'. Known profiles: prod, staging");
+    }
+
+    #[test]
+    fn profile_variable_wins_over_active_profile() {
+        let mut config = profiles_config();
+        config
+            .select_profile(|name| (name == "STARGATE_PROFILE").then(|| "prod".to_string()))
+            .unwrap();
+        assert_eq!(config.profile.as_deref(), Some("prod"));
+
+        let mut config = profiles_config();
+        config.select_profile(|_| Some(String::new())).unwrap();
+        assert_eq!(config.profile.as_deref(), Some("staging"));
+    }
+
+    #[test]
+    fn env_overrides_ignore_unset_and_empty_vars() {
+        let overrides = env_overrides(|name| match name {
+            "STARGATE_ALCHEMY_API_KEY" => Some(" from-env \n".to_string()),
+            "STARGATE_DEFAULT_NETWORK" => Some(String::new()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(
+            overrides,
+            vec![EnvOverride {
+                var: "STARGATE_ALCHEMY_API_KEY",
+                key: "api_key",
+                value: "from-env".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn env_overrides_win_over_profile_and_file() {
+        let mut config = Config {
+            profile: Some("staging".to_string()),
+            ..profiles_config()
+        };
+        config.overrides = env_overrides(|name| match name {
+            "STARGATE_ALCHEMY_API_KEY" => Some("container".to_string()),
+            "STARGATE_DEFAULT_NETWORK" => Some("optimism".to_string()),
+            _ => None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            config.resolve_api_key().unwrap().as_deref(),
+            Some("container")
+        );
+        assert_eq!(config.get_default_network(), "optimism");
+        assert_eq!(
+            config.env_override("default-network").map(|o| o.var),
+            Some("STARGATE_DEFAULT_NETWORK")
+        );
+    }
+
+    #[test]
+    fn set_key_rejects_a_sender_that_is_not_an_address() {
+        let mut config = Config::default();
+        let err = config
+            .set_key("networks.base.from", "deployer".into())
+            .unwrap_err();
+        assert!(err.starts_with("'deployer' is not an address"), "{}", err);
+
+        let from = "0x00000000000000000000000000000000000000aa";
+        config.set_key("networks.base.from", from.into()).unwrap();
+        assert_eq!(config.sender_for(find_network("base").unwrap()), Some(from));
+    }
+
+    #[test]
+    fn typed_env_overrides_are_checked() {
+        let overrides = |value: &str| {
+            env_overrides(|name| {
+                matches!(name, "STARGATE_PROVIDER" | "STARGATE_EXPORT_ADDRESSES")
+                    .then(|| value.to_string())
+            })
+        };
+        let err = overrides("yes").unwrap_err().to_string();
+        assert!(
+            err.starts_with("$STARGATE_PROVIDER=\"yes\": unknown variant `yes`"),
+            "{}",
+            err
+        );
+        assert!(overrides("local").is_err());
+
+        let invalid = invalid_env_overrides(|name| {
+            matches!(name, "STARGATE_PROVIDER" | "STARGATE_EXPORT_ADDRESSES")
+                .then(|| "yes".to_string())
+        });
+        assert_eq!(invalid.len(), 2);
+
+        let config = Config {
+            export_addresses: Some(false),
+            provider: Some(Provider::Alchemy),
+            overrides: env_overrides(|name| match name {
+                "STARGATE_PROVIDER" => Some("local".to_string()),
+                "STARGATE_EXPORT_ADDRESSES" => Some("true".to_string()),
+                _ => None,
+            })
+            .unwrap(),
+            ..Default::default()
+        };
+        assert!(config.exports_addresses());
+        assert_eq!(config.provider(), Provider::Local);
+    }
+
+    #[test]
+    fn local_provider_routes_every_network_to_the_local_node() {
+        let config = Config {
+            provider: Some(Provider::Local),
+            ..Default::default()
+        };
+        let mainnet = find_network("mainnet").unwrap();
+        assert!(!config.needs_api_key(mainnet));
+        assert_eq!(config.api_key_for(mainnet).unwrap(), "");
+        assert_eq!(config.rpc_url(mainnet, ""), LOCAL_RPC_URL);
+        assert_eq!(config.provider_for(mainnet), "local");
+        assert_eq!(Config::default().provider_for(mainnet), "alchemy");
+    }
+
+    #[test]
+    fn env_overrides_are_never_saved() {
+        let mut config = Config {
+            overrides: env_overrides(|name| match name {
+                "STARGATE_PROVIDER" => Some("local".to_string()),
+                "STARGATE_EXPORT_ADDRESSES" => Some("true".to_string()),
+                _ => Some("base".to_string()),
+            })
+            .unwrap(),
+            ..Default::default()
+        };
+        config
+            .set_key("networks.base.color", "blue".into())
+            .unwrap();
+
+        assert_eq!(config.overrides.len(), ENV_OVERRIDES.len());
+        assert!(config.default_network.is_none());
+        let saved = toml::to_string(&config).unwrap();
+        assert!(!saved.contains("default_network"));
+        assert!(!saved.contains("provider"));
+    }
 }
@@ -0,0 +1,168 @@
+use crate::paths;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Older entries are dropped once the file grows past this many lines.
+const MAX_ENTRIES: usize = 1000;
+
+/// One recorded switch, stored as a tab-separated line in the `history` file of the state directory.
+#[derive(Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub network: String,
+    pub cwd: String,
+}
+
+impl HistoryEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, '\t');
+        Some(Self {
+            timestamp: fields.next()?.parse().ok()?,
+            network: fields.next()?.to_string(),
+            cwd: fields.next().unwrap_or_default().to_string(),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}", self.timestamp, self.network, self.cwd)
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    paths::state_dir().map(|d| d.join("history"))
+}
+
+/// All recorded switches, oldest first. Unreadable lines are ignored.
+pub fn load() -> Vec<HistoryEntry> {
+    let Some(path) = path() else {
+        return Vec::new();
+    };
+
+    fs::read_to_string(path)
+        .map(|content| content.lines().filter_map(HistoryEntry::parse).collect())
+        .unwrap_or_default()
+}
+
+/// Appends a switch to the history file, trimming it when it grows too large.
+pub fn record(network: &str) -> Result<(), String> {
+    let path = path().ok_or("Could not determine home directory")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create state directory: {}", e))?;
+    }
+
+    let entry = HistoryEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        network: network.to_string(),
+        cwd: std::env::current_dir()
+            .map(|d| d.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+
+    let mut entries = load();
+    if entries.len() >= MAX_ENTRIES {
+        entries.drain(..entries.len() + 1 - MAX_ENTRIES / 2);
+        entries.push(entry);
+        let content: String = entries.iter().map(|e| e.to_line() + "\n").collect();
+        return fs::write(&path, content).map_err(|e| format!("Failed to write history: {}", e));
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history: {}", e))?;
+    writeln!(file, "{}", entry.to_line()).map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// The most recent network in `entries` other than `current`.
+pub fn previous_network<'a>(entries: &'a [HistoryEntry], current: Option<&str>) -> Option<&'a str> {
+    entries
+        .iter()
+        .rev()
+        .map(|e| e.network.as_str())
+        .find(|n| Some(*n) != current)
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM:SS` (UTC).
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(network: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 0,
+            network: network.to_string(),
+            cwd: "/tmp".to_string(),
+        }
+    }
+
+    #[test]
+    fn entry_roundtrips_through_line_format() {
+        let original = HistoryEntry {
+            timestamp: 1_700_000_000,
+            network: "base".to_string(),
+            cwd: "/home/me/my project".to_string(),
+        };
+        assert_eq!(HistoryEntry::parse(&original.to_line()), Some(original));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        assert!(HistoryEntry::parse("").is_none());
+        assert!(HistoryEntry::parse("not-a-timestamp\tbase\t/tmp").is_none());
+    }
+
+    #[test]
+    fn previous_network_skips_current() {
+        let entries = vec![entry("mainnet"), entry("base"), entry("arbitrum")];
+        assert_eq!(previous_network(&entries, Some("arbitrum")), Some("base"));
+        assert_eq!(previous_network(&entries, Some("base")), Some("arbitrum"));
+        assert_eq!(previous_network(&entries, None), Some("arbitrum"));
+    }
+
+    #[test]
+    fn previous_network_handles_empty_history() {
+        assert_eq!(previous_network(&[], Some("base")), None);
+        assert_eq!(previous_network(&[entry("base")], Some("base")), None);
+    }
+
+    #[test]
+    fn formats_timestamps_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20");
+    }
+}
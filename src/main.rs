@@ -1,13 +1,39 @@
+mod addresses;
 mod commands;
 mod config;
+mod history;
 mod networks;
+mod output;
+mod paths;
+mod picker;
+mod redact;
+mod registry;
+mod rpc;
+mod vault;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use commands::NetworkSet;
+use commands::completions::CompletionShell;
+use commands::env::EnvFormat;
+use commands::init::PromptStyle;
+use commands::list::ListArgs;
+use commands::prompt::PromptShell;
+use commands::switch::SwitchOptions;
+use output::{OutputFormat, parse_format};
 
 #[derive(Parser)]
 #[command(name = "stargate")]
 #[command(about = "Blockchain network switcher for Foundry", long_about = None)]
 struct Cli {
+    /// Use this profile from config.toml (overrides STARGATE_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Output format: table, plain, json or toml. For env and addr export: shell, dotenv,
+    /// compose, github, docker or json. For prompt: a template such as '{name}:{chain_id}'
+    #[arg(long, global = true)]
+    format: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -17,24 +43,60 @@ enum Commands {
     /// Install shell integration to config file
     #[command(name = "init")]
     Init {
-        /// Shell type (zsh, bash)
+        /// Shell type (zsh, bash), or 'starship' to print a Starship prompt module
         shell: String,
+        /// Also install a prompt segment showing the active network
+        #[arg(long, value_enum)]
+        prompt: Option<PromptStyle>,
     },
     /// Switch to a network
     #[command(visible_alias = "sw")]
     Switch {
-        /// Network name or alias (e.g., mainnet, eth, polygon, arb)
-        network: String,
-        /// Suppress output message
+        /// Network name or alias (e.g., mainnet, eth, polygon, arb), or '-' for the previous
+        /// network; omit to pick interactively
+        network: Option<String>,
+        /// Suppress output messages, including the protected-network banner
         #[arg(short, long)]
         silent: bool,
+        /// Accept a unique prefix of a network name or alias (e.g. poly)
+        #[arg(short, long)]
+        fuzzy: bool,
+        /// Don't record this switch in the history file
+        #[arg(long, hide = true)]
+        no_history: bool,
+        /// Don't ask for confirmation when switching to a protected network
+        #[arg(short, long)]
+        yes: bool,
     },
+    /// Leave the current network and restore the previous environment
+    Off,
     /// Print current network name and chain ID
     #[command(visible_alias = "c")]
-    Current,
+    Current {
+        /// Show the RPC URL including API keys
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Print a prompt segment for the active network (nothing when inactive)
+    Prompt {
+        /// Color the segment (red for protected, dim for local, or the configured color)
+        #[arg(long)]
+        color: bool,
+        /// Escape colors for this shell's prompt
+        #[arg(long, value_enum, default_value = "raw")]
+        shell: PromptShell,
+    },
     /// Print the currently active RPC URL
     #[command(name = "current-rpc", visible_alias = "rpc")]
-    CurrentRpc,
+    CurrentRpc {
+        /// Show API keys instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Decrypt the API key vault for this shell (use via `sg unlock`)
+    Unlock,
+    /// Forget the vault session of this shell (use via `sg lock`)
+    Lock,
     /// Switch to anvil (local network)
     Root {
         /// Suppress output message
@@ -50,8 +112,95 @@ enum Commands {
         #[arg(short, long)]
         print: bool,
     },
+    /// Show recently used networks
+    History {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show everything known about a network
+    Info {
+        /// Network name, alias or chain ID
+        network: String,
+        /// Also query the RPC for latest block, gas price and client version
+        #[arg(long)]
+        live: bool,
+        /// Show the RPC URL including API keys
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Print a network's environment variables for .env files, CI or docker
+    Env {
+        /// Network name, alias or chain ID
+        network: String,
+    },
     /// List all available networks
-    List,
+    List(ListArgs),
+    /// Run a command once per network, with that network's environment
+    Each {
+        #[command(flatten)]
+        networks: NetworkSet,
+        /// Stop at the first network where the command fails
+        #[arg(long)]
+        fail_fast: bool,
+        /// Don't ask for confirmation when running on a protected network
+        #[arg(short, long)]
+        yes: bool,
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Run a command with a network's environment, without switching the shell
+    Exec {
+        /// Network name, alias or chain ID
+        #[arg(index = 1)]
+        network: String,
+        /// Don't ask for confirmation when running on a protected network
+        #[arg(short, long)]
+        yes: bool,
+        /// Command and arguments, after `--`
+        #[arg(index = 2, last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Start anvil forking a network
+    Fork {
+        /// Network name, alias or chain ID
+        #[arg(index = 1)]
+        network: String,
+        /// Fork at this block instead of the latest
+        #[arg(long)]
+        block: Option<u64>,
+        /// Port for anvil to listen on
+        #[arg(long)]
+        port: Option<u16>,
+        /// Extra anvil arguments, after `--`
+        #[arg(index = 2, last = true)]
+        anvil_args: Vec<String>,
+    },
+    /// Check that RPC endpoints answer with the expected chain ID
+    Ping {
+        #[command(flatten)]
+        networks: NetworkSet,
+    },
+    /// Print shell completions (network names come from the merged registry)
+    Completions {
+        /// Target shell
+        #[arg(value_enum)]
+        shell: CompletionShell,
+        /// Command name to complete (use 'sg' for the shell wrapper)
+        #[arg(long, default_value = "stargate")]
+        bin: String,
+    },
+    /// Manage the address book of labelled addresses
+    Addr {
+        #[command(subcommand)]
+        action: AddrAction,
+    },
+    /// Manage custom networks imported from chain lists
+    Registry {
+        #[command(subcommand)]
+        action: RegistryAction,
+    },
     /// Manage configuration
     Config {
         #[command(subcommand)]
@@ -60,54 +209,209 @@ enum Commands {
 }
 
 #[derive(Subcommand)]
-enum ConfigAction {
-    /// Set a configuration value
-    Set {
-        #[command(subcommand)]
-        setting: ConfigSetting,
+enum AddrAction {
+    /// Save a label for an address, e.g. `usdc 0xA0b8... --network mainnet`
+    Add {
+        /// Letters, digits, '_' and '-', starting with a letter
+        label: String,
+        address: String,
+        /// Only use the label on this network (default: every network)
+        #[arg(long)]
+        network: Option<String>,
     },
-    /// Get a configuration value
-    Get {
-        #[command(subcommand)]
-        setting: ConfigGetter,
+    /// List saved labels
+    List {
+        /// Only show global labels and those of this network
+        #[arg(long)]
+        network: Option<String>,
+    },
+    /// Remove a label
+    Remove {
+        label: String,
+        /// Remove the label of this network instead of the global one
+        #[arg(long)]
+        network: Option<String>,
+    },
+    /// Print the labels of a network as ADDR_<LABEL> variables
+    Export {
+        /// Network name, alias or chain ID (default: the active network)
+        #[arg(long)]
+        network: Option<String>,
     },
 }
 
 #[derive(Subcommand)]
-enum ConfigSetting {
-    /// Set the Alchemy API key (prompts securely if not provided)
-    ApiKey { key: Option<String> },
-    /// Set the default network (used when starting new shells)
-    DefaultNetwork { network: String },
+enum RegistryAction {
+    /// Import networks from a chainlist / ethereum-lists JSON file
+    Import {
+        /// Path to the JSON file (a single chain or an array of chains)
+        file: String,
+        /// Only import these chain IDs (comma-separated or repeated)
+        #[arg(long = "chain-id", value_delimiter = ',')]
+        chain_ids: Vec<u64>,
+        /// Report what would be imported without writing the registry
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
-enum ConfigGetter {
-    /// Show the current default network
-    DefaultNetwork,
+enum ConfigAction {
+    /// Set a value by key path, e.g. `default-network base` or `networks.base.color blue`
+    Set {
+        /// Key path (`api-key` prompts securely, `default-network` is validated)
+        key: String,
+        /// TOML value; plain strings need no quotes
+        value: Option<String>,
+        /// Store api-key in the password-encrypted vault instead of config.toml
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Get a value by key path
+    Get {
+        key: String,
+        /// Show secrets instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Remove a value by key path
+    Unset { key: String },
+    /// Show every configured value (secrets masked)
+    List {
+        /// Show secrets instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Open config.toml in $EDITOR, validating it before saving
+    Edit,
+    /// Print the path of config.toml
+    Path {
+        /// Print the state directory (history) instead
+        #[arg(long, conflicts_with = "cache")]
+        state: bool,
+        /// Print the cache directory instead
+        #[arg(long)]
+        cache: bool,
+    },
+    /// Make a profile the default for new shells
+    UseProfile { name: String },
+    /// Check config.toml for errors and offer to repair them
+    Doctor {
+        /// Apply all fixes without asking
+        #[arg(long)]
+        fix: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
+    if let Some(profile) = cli.profile {
+        config::select_profile(profile);
+    }
 
+    let format = cli.format;
+    let output_format = || parse_format(format.as_deref(), OutputFormat::Table);
+    let env_format = || parse_format(format.as_deref(), EnvFormat::Shell);
     match cli.command {
-        Commands::Init { shell } => commands::init::run(&shell),
-        Commands::Switch { network, silent } => commands::switch::run(&network, silent),
-        Commands::Current => commands::current::run(),
-        Commands::CurrentRpc => commands::current_rpc::run(),
-        Commands::Root { silent } => commands::switch::run("anvil", silent),
-        Commands::Explorer { target, print } => commands::explorer::run(target.as_deref(), print),
-        Commands::List => commands::list::run(),
-        Commands::Config { action } => match action {
-            ConfigAction::Set { setting } => match setting {
-                ConfigSetting::ApiKey { key } => commands::config::set_api_key(key),
-                ConfigSetting::DefaultNetwork { network } => {
-                    commands::config::set_default_network(network)
-                }
+        Commands::Init { shell, prompt } => commands::init::run(&shell, prompt),
+        Commands::Switch {
+            network,
+            silent,
+            fuzzy,
+            no_history,
+            yes,
+        } => commands::switch::run(
+            network.as_deref(),
+            SwitchOptions {
+                silent,
+                fuzzy,
+                no_history,
+                yes,
             },
-            ConfigAction::Get { setting } => match setting {
-                ConfigGetter::DefaultNetwork => commands::config::get_default_network(),
+        ),
+        Commands::Off => commands::off::run(),
+        Commands::Current { reveal } => commands::current::run(output_format(), reveal),
+        Commands::Prompt { color, shell } => {
+            commands::prompt::run(format.as_deref().unwrap_or("{name}"), color, shell)
+        }
+        Commands::Unlock => commands::vault::unlock(),
+        Commands::Lock => commands::vault::lock(),
+        Commands::CurrentRpc { reveal } => commands::current_rpc::run(reveal),
+        Commands::Root { silent } => commands::switch::run(
+            Some("anvil"),
+            SwitchOptions {
+                silent,
+                ..Default::default()
             },
+        ),
+        Commands::Explorer { target, print } => commands::explorer::run(target.as_deref(), print),
+        Commands::History { limit } => commands::history::run(limit),
+        Commands::Info {
+            network,
+            live,
+            reveal,
+        } => commands::info::run(&network, live, reveal, output_format()),
+        Commands::Env { network } => commands::env::run(&network, env_format()),
+        Commands::List(args) => commands::list::run(args, output_format()),
+        Commands::Each {
+            networks,
+            fail_fast,
+            yes,
+            command,
+        } => commands::each::run(&networks, fail_fast, yes, &command),
+        Commands::Exec {
+            network,
+            yes,
+            command,
+        } => commands::exec::run(&network, yes, &command),
+        Commands::Fork {
+            network,
+            block,
+            port,
+            anvil_args,
+        } => commands::exec::fork(&network, block, port, &anvil_args),
+        Commands::Ping { networks } => commands::ping::run(&networks, output_format()),
+        Commands::Completions { shell, bin } => {
+            commands::completions::run(Cli::command(), shell, &bin)
+        }
+        Commands::Addr { action } => match action {
+            AddrAction::Add {
+                label,
+                address,
+                network,
+            } => commands::addr::add(&label, &address, network.as_deref()),
+            AddrAction::List { network } => {
+                commands::addr::list(network.as_deref(), output_format())
+            }
+            AddrAction::Remove { label, network } => {
+                commands::addr::remove(&label, network.as_deref())
+            }
+            AddrAction::Export { network } => {
+                commands::addr::export(network.as_deref(), env_format())
+            }
+        },
+        Commands::Registry { action } => match action {
+            RegistryAction::Import {
+                file,
+                chain_ids,
+                dry_run,
+            } => commands::registry::import(&file, &chain_ids, dry_run),
+        },
+        Commands::Config { action } => match action {
+            ConfigAction::Set {
+                key,
+                value,
+                encrypt,
+            } => commands::config::set(&key, value, encrypt),
+            ConfigAction::Get { key, reveal } => {
+                commands::config::get(&key, reveal, output_format())
+            }
+            ConfigAction::Unset { key } => commands::config::unset(&key),
+            ConfigAction::List { reveal } => commands::config::list(reveal, output_format()),
+            ConfigAction::Edit => commands::config::edit(),
+            ConfigAction::Path { state, cache } => commands::config::path(state, cache),
+            ConfigAction::UseProfile { name } => commands::config::use_profile(&name),
+            ConfigAction::Doctor { fix } => commands::doctor::run(fix),
         },
     }
 }
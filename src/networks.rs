@@ -1,19 +1,70 @@
+use crate::registry;
+use serde::Serialize;
+
+/// The local node (anvil) networks without an RPC provider point at.
+pub const LOCAL_RPC_URL: &str = "http://127.0.0.1:8545";
+
+/// Who verifies contracts for a network's explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verifier {
+    /// The multichain Etherscan v2 API
+    Etherscan,
+    /// The explorer's own Blockscout API
+    Blockscout,
+}
+
+#[derive(Debug)]
 pub struct Network {
     pub name: &'static str,
     pub aliases: &'static [&'static str],
     pub chain_id: u64,
     pub alchemy_subdomain: Option<&'static str>,
     pub explorer_url: Option<&'static str>,
+    /// Contract verification service for the explorer, where known.
+    pub verifier: Option<Verifier>,
+    /// Fixed RPC endpoint for networks not served by Alchemy (e.g. imported ones).
+    pub custom_rpc: Option<&'static str>,
 }
 
 impl Network {
     pub fn rpc_url(&self, api_key: &str) -> String {
-        match self.alchemy_subdomain {
-            Some(subdomain) => format!("https://{}.g.alchemy.com/v2/{}", subdomain, api_key),
-            None => "http://127.0.0.1:8545".to_string(),
+        match (self.alchemy_subdomain, self.custom_rpc) {
+            (Some(subdomain), _) => format!("https://{}.g.alchemy.com/v2/{}", subdomain, api_key),
+            (None, Some(rpc)) => rpc.to_string(),
+            (None, None) => LOCAL_RPC_URL.to_string(),
+        }
+    }
+
+    /// Where the RPC endpoint comes from: "alchemy", "custom" (imported) or "local".
+    pub fn provider(&self) -> &'static str {
+        match (self.alchemy_subdomain, self.custom_rpc) {
+            (Some(_), _) => "alchemy",
+            (None, Some(_)) => "custom",
+            (None, None) => "local",
         }
     }
 
+    /// Contract verification API for `forge verify-contract --verifier-url`; `None`
+    /// when the network's verifier isn't known.
+    pub fn verifier_url(&self) -> Option<String> {
+        match self.verifier? {
+            Verifier::Etherscan => Some(format!(
+                "https://api.etherscan.io/v2/api?chainid={}",
+                self.chain_id
+            )),
+            Verifier::Blockscout => {
+                let explorer = self.explorer_url?.trim_end_matches('/');
+                Some(format!("{}/api/", explorer))
+            }
+        }
+    }
+
+    /// The canonical name followed by all aliases.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.name).chain(self.aliases.iter().copied())
+    }
+
     pub fn matches(&self, query: &str) -> bool {
         let query = query.to_lowercase();
         if self.name == query {
@@ -29,6 +80,9 @@ impl Network {
     }
 }
 
+/// Networks that require confirmation unless configured otherwise.
+pub const DEFAULT_PROTECTED: &[&str] = &["mainnet"];
+
 pub static NETWORKS: &[Network] = &[
     Network {
         name: "mainnet",
@@ -36,6 +90,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 1,
         alchemy_subdomain: Some("eth-mainnet"),
         explorer_url: Some("https://etherscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "polygon",
@@ -43,6 +99,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 137,
         alchemy_subdomain: Some("polygon-mainnet"),
         explorer_url: Some("https://polygonscan.com"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "optimism",
@@ -50,6 +108,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 10,
         alchemy_subdomain: Some("opt-mainnet"),
         explorer_url: Some("https://optimistic.etherscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "arbitrum",
@@ -57,6 +117,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 42161,
         alchemy_subdomain: Some("arb-mainnet"),
         explorer_url: Some("https://arbiscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "base",
@@ -64,6 +126,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 8453,
         alchemy_subdomain: Some("base-mainnet"),
         explorer_url: Some("https://basescan.org"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "bnb",
@@ -71,6 +135,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 56,
         alchemy_subdomain: Some("bnb-mainnet"),
         explorer_url: Some("https://bscscan.com"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "linea",
@@ -78,6 +144,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 59144,
         alchemy_subdomain: Some("linea-mainnet"),
         explorer_url: Some("https://lineascan.build"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "ink",
@@ -85,6 +153,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 57073,
         alchemy_subdomain: Some("ink-mainnet"),
         explorer_url: Some("https://explorer.inkonchain.com"),
+        verifier: Some(Verifier::Blockscout),
+        custom_rpc: None,
     },
     Network {
         name: "arbnova",
@@ -92,6 +162,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 42170,
         alchemy_subdomain: Some("arbnova-mainnet"),
         explorer_url: Some("https://nova.arbiscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "zksync",
@@ -99,6 +171,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 324,
         alchemy_subdomain: Some("zksync-mainnet"),
         explorer_url: Some("https://explorer.zksync.io"),
+        verifier: None,
+        custom_rpc: None,
     },
     Network {
         name: "polygon-zkevm",
@@ -106,6 +180,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 1101,
         alchemy_subdomain: Some("polygonzkevm-mainnet"),
         explorer_url: Some("https://zkevm.polygonscan.com"),
+        verifier: None,
+        custom_rpc: None,
     },
     Network {
         name: "avalanche",
@@ -113,6 +189,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 43114,
         alchemy_subdomain: Some("avax-mainnet"),
         explorer_url: Some("https://snowtrace.io"),
+        verifier: None,
+        custom_rpc: None,
     },
     Network {
         name: "gnosis",
@@ -120,6 +198,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 100,
         alchemy_subdomain: Some("gnosis-mainnet"),
         explorer_url: Some("https://gnosisscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "scroll",
@@ -127,6 +207,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 534352,
         alchemy_subdomain: Some("scroll-mainnet"),
         explorer_url: Some("https://scrollscan.com"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "celo",
@@ -134,6 +216,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 42220,
         alchemy_subdomain: Some("celo-mainnet"),
         explorer_url: Some("https://celoscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "mantle",
@@ -141,6 +225,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 5000,
         alchemy_subdomain: Some("mantle-mainnet"),
         explorer_url: Some("https://mantlescan.xyz"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "blast",
@@ -148,6 +234,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 81457,
         alchemy_subdomain: Some("blast-mainnet"),
         explorer_url: Some("https://blastscan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "sonic",
@@ -155,6 +243,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 146,
         alchemy_subdomain: Some("sonic-mainnet"),
         explorer_url: Some("https://sonicscan.org"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "unichain",
@@ -162,6 +252,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 130,
         alchemy_subdomain: Some("unichain-mainnet"),
         explorer_url: Some("https://unichain.blockscout.com/"),
+        verifier: Some(Verifier::Blockscout),
+        custom_rpc: None,
     },
     Network {
         name: "flow",
@@ -169,6 +261,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 747,
         alchemy_subdomain: Some("flow-mainnet"),
         explorer_url: Some("https://evm.flowscan.io"),
+        verifier: Some(Verifier::Blockscout),
+        custom_rpc: None,
     },
     Network {
         name: "worldchain",
@@ -176,6 +270,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 480,
         alchemy_subdomain: Some("worldchain-mainnet"),
         explorer_url: Some("https://worldscan.org"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "apechain",
@@ -183,6 +279,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 33139,
         alchemy_subdomain: Some("apechain-mainnet"),
         explorer_url: Some("https://apescan.io"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "abstract",
@@ -190,6 +288,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 2741,
         alchemy_subdomain: Some("abstract-mainnet"),
         explorer_url: Some("https://abscan.org"),
+        verifier: Some(Verifier::Etherscan),
+        custom_rpc: None,
     },
     Network {
         name: "hyperevm",
@@ -197,6 +297,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 999,
         alchemy_subdomain: Some("hyperevm-mainnet"),
         explorer_url: Some("https://hyperscan.com"),
+        verifier: None,
+        custom_rpc: None,
     },
     Network {
         name: "mode",
@@ -204,6 +306,8 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 34443,
         alchemy_subdomain: Some("mode-mainnet"),
         explorer_url: Some("https://explorer.mode.network"),
+        verifier: Some(Verifier::Blockscout),
+        custom_rpc: None,
     },
     Network {
         name: "anvil",
@@ -211,11 +315,114 @@ pub static NETWORKS: &[Network] = &[
         chain_id: 31337,
         alchemy_subdomain: None,
         explorer_url: None,
+        verifier: None,
+        custom_rpc: None,
     },
 ];
 
+/// Iterates over the built-in networks followed by the ones imported into the user registry.
+pub fn all_networks() -> impl Iterator<Item = &'static Network> {
+    NETWORKS.iter().chain(registry::custom_networks().iter())
+}
+
 pub fn find_network(query: &str) -> Option<&'static Network> {
-    NETWORKS.iter().find(|n| n.matches(query))
+    all_networks().find(|n| n.matches(query))
+}
+
+/// Like `find_network`, but falls back to a unique prefix match on names and aliases
+/// (`poly` -> polygon). Ambiguous and unknown queries produce a descriptive error.
+pub fn find_network_fuzzy(query: &str) -> Result<&'static Network, String> {
+    if let Some(network) = find_network(query) {
+        return Ok(network);
+    }
+
+    let query = query.to_lowercase();
+    let candidates: Vec<&'static Network> = all_networks()
+        .filter(|n| !query.is_empty() && n.names().any(|name| name.starts_with(&query)))
+        .collect();
+
+    // `poly` matches polygon and polygon-zkevm; the shorter name is the obvious pick
+    let shortest = candidates
+        .iter()
+        .find(|c| candidates.iter().all(|n| n.name.starts_with(c.name)));
+
+    match (candidates.as_slice(), shortest) {
+        ([], _) => Err(unknown_network_message(&query)),
+        (_, Some(network)) => Ok(network),
+        _ => {
+            let names: Vec<&str> = candidates.iter().map(|n| n.name).collect();
+            Err(format!(
+                "Ambiguous network: '{}' matches {}",
+                query,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Error message for an unknown network, with the closest names as suggestions.
+pub fn unknown_network_message(query: &str) -> String {
+    match suggest(query).as_slice() {
+        [] => format!("Unknown network: {}.", query),
+        [only] => format!("Unknown network: {}. Did you mean '{}'?", query, only),
+        many => format!(
+            "Unknown network: {}. Did you mean one of: {}?",
+            query,
+            many.join(", ")
+        ),
+    }
+}
+
+/// Ranks networks by edit distance between `query` and their names/aliases,
+/// returning up to three canonical names that are plausibly what was meant.
+pub fn suggest(query: &str) -> Vec<&'static str> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let max_distance = (query.len() / 3).max(1);
+
+    let mut ranked: Vec<(usize, &'static str)> = all_networks()
+        .filter_map(|n| {
+            n.names()
+                .map(|name| {
+                    if name.starts_with(&query) {
+                        0
+                    } else {
+                        edit_distance(&query, name)
+                    }
+                })
+                .min()
+                .filter(|d| *d <= max_distance)
+                .map(|d| (d, n.name))
+        })
+        .collect();
+    ranked.sort_by_key(|(distance, _)| *distance);
+
+    ranked.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+/// Levenshtein distance between two strings.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// `0x` followed by 40 hex digits (no checksum validation).
+pub(crate) fn is_address(s: &str) -> bool {
+    s.strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
 }
 
 #[cfg(test)]
@@ -313,6 +520,76 @@ mod tests {
         assert!(find_network("").is_none());
     }
 
+    // ==================== Fuzzy matching tests ====================
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("arbitrum", "arbitrum"), 0);
+        assert_eq!(edit_distance("arbitrm", "arbitrum"), 1);
+        assert_eq!(edit_distance("polgyon", "polygon"), 2);
+        assert_eq!(edit_distance("", "base"), 4);
+    }
+
+    #[test]
+    fn suggest_finds_typos() {
+        assert_eq!(suggest("arbitrm")[0], "arbitrum");
+        assert_eq!(suggest("mainet")[0], "mainnet");
+        assert_eq!(suggest("Optimsm")[0], "optimism");
+    }
+
+    #[test]
+    fn suggest_matches_aliases_but_returns_canonical_names() {
+        assert_eq!(suggest("etherium")[0], "mainnet");
+    }
+
+    #[test]
+    fn suggest_returns_nothing_for_unrelated_queries() {
+        assert!(suggest("zzzzzzzzzz").is_empty());
+        assert!(suggest("").is_empty());
+    }
+
+    #[test]
+    fn unknown_network_message_includes_suggestion() {
+        let message = unknown_network_message("arbitrm");
+        assert!(message.contains("Unknown network: arbitrm"));
+        assert!(message.contains("Did you mean 'arbitrum'?"));
+
+        let message = unknown_network_message("zzzzzzzzzz");
+        assert_eq!(message, "Unknown network: zzzzzzzzzz.");
+    }
+
+    #[test]
+    fn fuzzy_prefers_exact_matches() {
+        // "arb" is an exact alias even though it also prefixes "arbnova"
+        assert_eq!(find_network_fuzzy("arb").unwrap().name, "arbitrum");
+    }
+
+    #[test]
+    fn fuzzy_selects_unique_prefix() {
+        assert_eq!(find_network_fuzzy("opt").unwrap().name, "optimism");
+        assert_eq!(find_network_fuzzy("opti").unwrap().name, "optimism");
+        assert_eq!(find_network_fuzzy("hyperl").unwrap().name, "hyperevm");
+    }
+
+    #[test]
+    fn fuzzy_prefers_name_that_prefixes_all_candidates() {
+        assert_eq!(find_network_fuzzy("poly").unwrap().name, "polygon");
+    }
+
+    #[test]
+    fn fuzzy_reports_ambiguous_prefixes() {
+        let err = find_network_fuzzy("ar").unwrap_err();
+        assert!(err.contains("Ambiguous network"));
+        assert!(err.contains("arbitrum"));
+        assert!(err.contains("arbnova"));
+    }
+
+    #[test]
+    fn fuzzy_reports_unknown_networks_with_suggestions() {
+        let err = find_network_fuzzy("arbitrm").unwrap_err();
+        assert!(err.contains("Did you mean 'arbitrum'?"));
+    }
+
     // ==================== Network::rpc_url() tests ====================
 
     #[test]
@@ -351,6 +628,33 @@ mod tests {
         assert_eq!(url, "http://127.0.0.1:8545");
     }
 
+    // ==================== Network::verifier_url() tests ====================
+
+    #[test]
+    fn verifier_url_uses_etherscan_v2_by_default() {
+        let arbitrum = find_network("arbitrum").unwrap();
+        assert_eq!(
+            arbitrum.verifier_url(),
+            Some("https://api.etherscan.io/v2/api?chainid=42161".to_string())
+        );
+    }
+
+    #[test]
+    fn verifier_url_uses_blockscout_api() {
+        let unichain = find_network("unichain").unwrap();
+        assert_eq!(
+            unichain.verifier_url(),
+            Some("https://unichain.blockscout.com/api/".to_string())
+        );
+    }
+
+    #[test]
+    fn verifier_url_is_none_when_unknown() {
+        assert!(find_network("anvil").unwrap().verifier_url().is_none());
+        assert!(find_network("zksync").unwrap().verifier_url().is_none());
+        assert!(find_network("hyperevm").unwrap().verifier_url().is_none());
+    }
+
     // ==================== Network data integrity tests ====================
 
     #[test]
@@ -396,4 +700,12 @@ mod tests {
         names.dedup();
         assert_eq!(names.len(), original_len, "Network names should be unique");
     }
+
+    #[test]
+    fn is_address_requires_0x_and_40_hex_digits() {
+        assert!(is_address("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"));
+        assert!(!is_address("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"));
+        assert!(!is_address("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB4"));
+        assert!(!is_address("0xZ0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"));
+    }
 }
@@ -0,0 +1,142 @@
+use crate::config::Config;
+use crate::networks::Network;
+use serde::Serialize;
+
+/// Output format shared by commands that print data scripts may want to parse.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table
+    #[default]
+    Table,
+    /// Tab-separated values without headers
+    Plain,
+    Json,
+    Toml,
+}
+
+/// Reads the global `--format` as one of a command's formats `T`, or `default` when
+/// it wasn't given. Exits when the command doesn't support the format.
+pub fn parse_format<T: clap::ValueEnum>(format: Option<&str>, default: T) -> T {
+    let Some(format) = format else {
+        return default;
+    };
+    T::from_str(format, true).unwrap_or_else(|_| {
+        let supported: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        eprintln!(
+            "This command doesn't support --format {}. Use one of: {}.",
+            format,
+            supported.join(", ")
+        );
+        std::process::exit(1);
+    })
+}
+
+/// Serializable view of a network together with its user settings. Only the public
+/// fields are listed, so internal ones like the Alchemy subdomain stay out of scripts.
+#[derive(Debug, Serialize)]
+pub struct NetworkView<'a> {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub chain_id: u64,
+    pub explorer_url: Option<&'static str>,
+    pub tags: Vec<&'a str>,
+    pub protected: bool,
+}
+
+impl<'a> NetworkView<'a> {
+    pub fn new(network: &Network, config: &'a Config) -> Self {
+        Self {
+            name: network.name,
+            aliases: network.aliases,
+            chain_id: network.chain_id,
+            explorer_url: network.explorer_url,
+            tags: config.tags_for(network),
+            protected: config.is_protected(network),
+        }
+    }
+}
+
+/// Renders `value` as JSON or TOML. TOML needs a table at the top level, so callers
+/// wrap lists in a struct.
+pub fn serialize<T: Serialize>(value: &T, format: OutputFormat) -> Result<String, String> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize JSON: {}", e)),
+        OutputFormat::Toml => {
+            toml::to_string_pretty(value).map_err(|e| format!("Failed to serialize TOML: {}", e))
+        }
+        OutputFormat::Table | OutputFormat::Plain => {
+            Err("Table and plain output are rendered by each command".to_string())
+        }
+    }
+}
+
+/// Prints `value` as JSON or TOML, exiting on serialization errors.
+pub fn print_serialized<T: Serialize>(value: &T, format: OutputFormat) {
+    match serialize(value, format) {
+        Ok(output) => println!("{}", output.trim_end()),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::find_network;
+
+    #[derive(Serialize)]
+    struct Networks<'a> {
+        networks: Vec<NetworkView<'a>>,
+    }
+
+    #[test]
+    fn network_view_serializes_to_json() {
+        let config = Config::default();
+        let view = NetworkView::new(find_network("mainnet").unwrap(), &config);
+        let json: serde_json::Value =
+            serde_json::from_str(&serialize(&view, OutputFormat::Json).unwrap()).unwrap();
+
+        assert_eq!(json["name"], "mainnet");
+        assert_eq!(json["chain_id"], 1);
+        assert_eq!(json["aliases"][0], "eth");
+        assert_eq!(json["explorer_url"], "https://etherscan.io");
+        assert_eq!(json["protected"], true);
+        assert!(json.get("alchemy_subdomain").is_none());
+        assert!(json.get("custom_rpc").is_none());
+    }
+
+    #[test]
+    fn network_views_serialize_to_toml() {
+        let config = Config::default();
+        let networks = Networks {
+            networks: vec![NetworkView::new(find_network("anvil").unwrap(), &config)],
+        };
+        let toml_str = serialize(&networks, OutputFormat::Toml).unwrap();
+
+        assert!(toml_str.contains("[[networks]]"));
+        assert!(toml_str.contains("name = \"anvil\""));
+        assert!(toml_str.contains("chain_id = 31337"));
+        assert!(!toml_str.contains("explorer_url"));
+    }
+
+    #[test]
+    fn format_flag_falls_back_to_the_command_default() {
+        assert_eq!(parse_format(None, OutputFormat::Table), OutputFormat::Table);
+        assert_eq!(
+            parse_format(Some("JSON"), OutputFormat::Table),
+            OutputFormat::Json
+        );
+    }
+
+    #[test]
+    fn table_format_is_not_serialized() {
+        assert!(serialize(&1, OutputFormat::Table).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Where stargate keeps its files.
+#[derive(Debug, PartialEq)]
+struct Layout {
+    /// Config, registry and vault
+    config_dir: PathBuf,
+    config_file: PathBuf,
+    /// History
+    state_dir: PathBuf,
+    /// RPC caches, safe to delete
+    cache_dir: PathBuf,
+}
+
+/// Resolves the layout from environment variables (via `var`) and the home directory:
+///
+/// 1. `$STARGATE_HOME` holds everything; `$STARGATE_CONFIG` overrides the config file alone.
+/// 2. An existing `~/.stargate` keeps being used for everything.
+/// 3. Otherwise `$XDG_CONFIG_HOME/stargate`, `$XDG_STATE_HOME/stargate` and
+///    `$XDG_CACHE_HOME/stargate`, defaulting to `~/.config`, `~/.local/state` and `~/.cache`.
+///
+/// Outside XDG the cache is the `cache` subdirectory, so it can be deleted on its own.
+fn resolve(var: impl Fn(&str) -> Option<String>, home: Option<&Path>) -> Option<Layout> {
+    let var = |name: &str| var(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let xdg = |name: &str, default: &str| {
+        var(name)
+            .or_else(|| home.map(|h| h.join(default)))
+            .map(|d| d.join("stargate"))
+    };
+
+    let (config_dir, state_dir, cache_dir) = if let Some(dir) = var("STARGATE_HOME") {
+        (dir.clone(), dir.clone(), dir.join("cache"))
+    } else if let Some(legacy) = home.map(|h| h.join(".stargate")).filter(|d| d.is_dir()) {
+        (legacy.clone(), legacy.clone(), legacy.join("cache"))
+    } else {
+        (
+            xdg("XDG_CONFIG_HOME", ".config")?,
+            xdg("XDG_STATE_HOME", ".local/state")?,
+            xdg("XDG_CACHE_HOME", ".cache")?,
+        )
+    };
+
+    Some(Layout {
+        config_file: var("STARGATE_CONFIG").unwrap_or_else(|| config_dir.join("config.toml")),
+        config_dir,
+        state_dir,
+        cache_dir,
+    })
+}
+
+fn layout() -> Option<Layout> {
+    resolve(|name| env::var(name).ok(), dirs::home_dir().as_deref())
+}
+
+/// Directory for the registry and vault.
+pub fn config_dir() -> Option<PathBuf> {
+    layout().map(|l| l.config_dir)
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    layout().map(|l| l.config_file)
+}
+
+/// Directory for history and other state that isn't configuration.
+pub fn state_dir() -> Option<PathBuf> {
+    layout().map(|l| l.state_dir)
+}
+
+/// Directory for RPC caches.
+pub fn cache_dir() -> Option<PathBuf> {
+    layout().map(|l| l.cache_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fs;
+
+    fn resolve_with(vars: &[(&str, &str)], home: &Path) -> Layout {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        resolve(|name| vars.get(name).cloned(), Some(home)).unwrap()
+    }
+
+    fn temp_home(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!("stargate-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    #[test]
+    fn defaults_to_xdg_directories() {
+        let home = temp_home("xdg-default");
+        let layout = resolve_with(&[], &home);
+        assert_eq!(
+            layout.config_file,
+            home.join(".config/stargate/config.toml")
+        );
+        assert_eq!(layout.state_dir, home.join(".local/state/stargate"));
+        assert_eq!(layout.cache_dir, home.join(".cache/stargate"));
+
+        let layout = resolve_with(
+            &[
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+                ("XDG_STATE_HOME", "/xdg/state"),
+                ("XDG_CACHE_HOME", "/xdg/cache"),
+            ],
+            &home,
+        );
+        assert_eq!(layout.config_dir, PathBuf::from("/xdg/config/stargate"));
+        assert_eq!(layout.state_dir, PathBuf::from("/xdg/state/stargate"));
+        assert_eq!(layout.cache_dir, PathBuf::from("/xdg/cache/stargate"));
+    }
+
+    #[test]
+    fn existing_legacy_directory_wins_over_xdg() {
+        let home = temp_home("legacy");
+        fs::create_dir(home.join(".stargate")).unwrap();
+
+        let layout = resolve_with(&[("XDG_CONFIG_HOME", "/xdg/config")], &home);
+        assert_eq!(layout.config_file, home.join(".stargate/config.toml"));
+        assert_eq!(layout.state_dir, home.join(".stargate"));
+        assert_eq!(layout.cache_dir, home.join(".stargate/cache"));
+    }
+
+    #[test]
+    fn stargate_overrides_win() {
+        let home = temp_home("overrides");
+        fs::create_dir(home.join(".stargate")).unwrap();
+
+        let layout = resolve_with(&[("STARGATE_HOME", "/sg")], &home);
+        assert_eq!(layout.config_file, PathBuf::from("/sg/config.toml"));
+        assert_eq!(layout.state_dir, PathBuf::from("/sg"));
+        assert_eq!(layout.cache_dir, PathBuf::from("/sg/cache"));
+
+        let layout = resolve_with(&[("STARGATE_CONFIG", "/ci/stargate.toml")], &home);
+        assert_eq!(layout.config_file, PathBuf::from("/ci/stargate.toml"));
+        assert_eq!(layout.config_dir, home.join(".stargate"));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::config::Config;
+use crate::networks::{Network, all_networks};
+use crossterm::cursor::{MoveToColumn, MoveUp};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{QueueableCommand, execute};
+use std::io::{self, IsTerminal, Write};
+
+const MAX_ROWS: usize = 10;
+
+/// Networks whose name, aliases, chain ID or tags contain `query` (case-insensitive).
+pub fn filter<'a>(networks: &[&'a Network], query: &str, config: &Config) -> Vec<&'a Network> {
+    let query = query.trim().to_lowercase();
+
+    networks
+        .iter()
+        .filter(|n| {
+            query.is_empty()
+                || n.names().any(|name| name.contains(&query))
+                || n.chain_id.to_string().contains(&query)
+                || config.tags_for(n).iter().any(|t| t.contains(&query))
+        })
+        .copied()
+        .collect()
+}
+
+/// Lets the user pick a network interactively. The UI is drawn on stderr so stdout
+/// stays clean for `eval`. Returns `Ok(None)` if the user cancels.
+pub fn pick(config: &Config) -> Result<Option<&'static Network>, String> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err("No network given and no terminal available for the picker.".to_string());
+    }
+
+    let networks: Vec<&'static Network> = all_networks().collect();
+
+    terminal::enable_raw_mode().map_err(|e| format!("Failed to start picker: {}", e))?;
+    let result = run_picker(&networks, config);
+    let _ = terminal::disable_raw_mode();
+
+    result.map_err(|e| format!("Picker failed: {}", e))
+}
+
+fn run_picker(
+    networks: &[&'static Network],
+    config: &Config,
+) -> io::Result<Option<&'static Network>> {
+    let mut stderr = io::stderr();
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = filter(networks, &query, config);
+        selected = selected.min(matches.len().saturating_sub(1));
+        draw(&mut stderr, &query, &matches, selected, config)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        match (code, modifiers) {
+            (KeyCode::Enter, _) => {
+                clear(&mut stderr)?;
+                return Ok(matches.get(selected).copied());
+            }
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                clear(&mut stderr)?;
+                return Ok(None);
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                selected = selected.saturating_sub(1);
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                selected += 1;
+            }
+            (KeyCode::Backspace, _) => {
+                query.pop();
+                selected = 0;
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Redraws the prompt and the visible window of matches, leaving the cursor on the
+/// prompt line so the next frame can erase everything below it.
+fn draw(
+    out: &mut impl Write,
+    query: &str,
+    matches: &[&Network],
+    selected: usize,
+    config: &Config,
+) -> io::Result<()> {
+    clear(out)?;
+
+    out.queue(Print(format!("Switch to: {}", query)))?;
+
+    let offset = selected.saturating_sub(MAX_ROWS - 1);
+    let visible = matches.iter().enumerate().skip(offset).take(MAX_ROWS);
+    let mut lines = 0;
+
+    for (i, network) in visible {
+        let tags = config.tags_for(network);
+        let mut row = format!("{:<16} {:>10}", network.name, network.chain_id);
+        if !network.aliases.is_empty() {
+            row.push_str(&format!("  ({})", network.aliases.join(", ")));
+        }
+        if !tags.is_empty() {
+            row.push_str(&format!("  [{}]", tags.join(", ")));
+        }
+
+        out.queue(Print("\r\n"))?;
+        if i == selected {
+            out.queue(SetAttribute(Attribute::Reverse))?;
+            out.queue(Print(format!("> {}", row)))?;
+            out.queue(SetAttribute(Attribute::Reset))?;
+        } else {
+            out.queue(Print(format!("  {}", row)))?;
+        }
+        lines += 1;
+    }
+
+    if matches.is_empty() {
+        out.queue(Print("\r\n  (no matching networks)"))?;
+        lines += 1;
+    }
+
+    // Park the cursor at the end of the prompt line
+    if lines > 0 {
+        out.queue(MoveUp(lines as u16))?;
+    }
+    out.queue(MoveToColumn(
+        ("Switch to: ".len() + query.chars().count()) as u16,
+    ))?;
+    out.flush()
+}
+
+fn clear(out: &mut impl Write) -> io::Result<()> {
+    execute!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(networks: &[&Network]) -> Vec<&'static str> {
+        networks.iter().map(|n| n.name).collect()
+    }
+
+    #[test]
+    fn empty_query_keeps_every_network() {
+        let networks: Vec<&Network> = all_networks().collect();
+        let config = Config::default();
+        assert_eq!(filter(&networks, "", &config).len(), networks.len());
+    }
+
+    #[test]
+    fn filters_by_name_alias_and_chain_id() {
+        let networks: Vec<&Network> = all_networks().collect();
+        let config = Config::default();
+
+        assert!(names(&filter(&networks, "arbi", &config)).contains(&"arbitrum"));
+        assert_eq!(names(&filter(&networks, "xdai", &config)), vec!["gnosis"]);
+        assert_eq!(
+            names(&filter(&networks, "42161", &config)),
+            vec!["arbitrum"]
+        );
+    }
+
+    #[test]
+    fn filter_is_case_insensitive() {
+        let networks: Vec<&Network> = all_networks().collect();
+        let config = Config::default();
+        assert_eq!(
+            names(&filter(&networks, "AVAX", &config)),
+            vec!["avalanche"]
+        );
+    }
+
+    #[test]
+    fn filters_by_tag() {
+        let networks: Vec<&Network> = all_networks().collect();
+        let config: Config = toml::from_str("[networks.base]\ntags = [\"op-stack\"]").unwrap();
+        assert_eq!(names(&filter(&networks, "op-st", &config)), vec!["base"]);
+    }
+}
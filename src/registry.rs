@@ -0,0 +1,435 @@
+use crate::config::write_atomic;
+use crate::networks::{NETWORKS, Network};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// A network imported into the user registry (`registry.toml` next to the config).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub chain_id: u64,
+    pub rpc_url: String,
+    pub explorer_url: Option<String>,
+    pub native_currency: Option<String>,
+}
+
+impl RegistryEntry {
+    fn to_network(&self) -> Network {
+        let aliases: Vec<&'static str> = self.aliases.iter().map(|a| leak(a)).collect();
+
+        Network {
+            name: leak(&self.name),
+            aliases: Box::leak(aliases.into_boxed_slice()),
+            chain_id: self.chain_id,
+            alchemy_subdomain: None,
+            explorer_url: self.explorer_url.as_deref().map(leak),
+            verifier: None,
+            custom_rpc: Some(leak(&self.rpc_url)),
+        }
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(|a| a.as_str()))
+    }
+}
+
+/// Networks are looked up for the lifetime of the process, so leaking is fine here.
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    #[serde(default)]
+    pub networks: Vec<RegistryEntry>,
+}
+
+/// Outcome of an import, reported back to the user.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    pub added: Vec<RegistryEntry>,
+    pub skipped: Vec<(String, String)>,
+    pub missing_chain_ids: Vec<u64>,
+}
+
+impl Registry {
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|d| d.join("registry.toml"))
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not determine home directory")?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize registry: {}", e))?;
+        write_atomic(&path, &content).map_err(|e| format!("Failed to write registry: {}", e))
+    }
+
+    /// Merges `entries` into the registry, recording in `report` anything skipped
+    /// because it would shadow a built-in or already registered network.
+    pub fn import(&mut self, entries: Vec<RegistryEntry>, report: &mut ImportReport) {
+        for mut entry in entries {
+            if let Some(builtin) = NETWORKS.iter().find(|n| n.chain_id == entry.chain_id) {
+                report
+                    .skipped
+                    .push((entry.name, format!("built-in network '{}'", builtin.name)));
+                continue;
+            }
+
+            if let Some(existing) = self.networks.iter().find(|n| n.chain_id == entry.chain_id) {
+                report.skipped.push((
+                    entry.name,
+                    format!("already imported as '{}'", existing.name),
+                ));
+                continue;
+            }
+
+            if self.is_taken(&entry.name) {
+                let reason = format!("name '{}' is already in use", entry.name);
+                report.skipped.push((entry.name, reason));
+                continue;
+            }
+
+            // A clashing alias is not worth losing the whole network over
+            entry.aliases.retain(|a| !self.is_taken(a));
+
+            report.added.push(entry.clone());
+            self.networks.push(entry);
+        }
+    }
+
+    fn is_taken(&self, name: &str) -> bool {
+        NETWORKS
+            .iter()
+            .any(|n| n.name == name || n.aliases.contains(&name))
+            || self.networks.iter().any(|e| e.names().any(|n| n == name))
+    }
+}
+
+/// Custom networks from the user registry, loaded once per process.
+pub fn custom_networks() -> &'static [Network] {
+    static CUSTOM: OnceLock<Vec<Network>> = OnceLock::new();
+
+    CUSTOM.get_or_init(|| match Registry::load() {
+        Ok(registry) => registry.networks.iter().map(|e| e.to_network()).collect(),
+        Err(e) => {
+            eprintln!("Warning: ignoring network registry: {}", e);
+            Vec::new()
+        }
+    })
+}
+
+// ==================== chainlist / ethereum-lists format ====================
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChainlistInput {
+    Many(Vec<ChainlistChain>),
+    One(Box<ChainlistChain>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainlistChain {
+    name: String,
+    chain_id: u64,
+    short_name: Option<String>,
+    #[serde(default)]
+    rpc: Vec<ChainlistRpc>,
+    native_currency: Option<ChainlistCurrency>,
+    #[serde(default)]
+    explorers: Vec<ChainlistExplorer>,
+}
+
+/// ethereum-lists uses plain strings, chainlist.org uses `{ "url": ... }` objects.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChainlistRpc {
+    Url(String),
+    Object { url: String },
+}
+
+impl ChainlistRpc {
+    fn url(&self) -> &str {
+        match self {
+            ChainlistRpc::Url(url) | ChainlistRpc::Object { url } => url,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChainlistCurrency {
+    symbol: String,
+}
+
+#[derive(Deserialize)]
+struct ChainlistExplorer {
+    url: String,
+}
+
+/// Parses a chainlist / ethereum-lists JSON document (a single chain or an array of chains).
+/// When `chain_ids` is non-empty only those chains are kept. Chains without a keyless
+/// HTTP(S) RPC endpoint and requested chain IDs absent from the file end up in the report.
+pub fn parse_chainlist(
+    json: &str,
+    chain_ids: &[u64],
+) -> Result<(Vec<RegistryEntry>, ImportReport), String> {
+    let input: ChainlistInput =
+        serde_json::from_str(json).map_err(|e| format!("Invalid chain list JSON: {}", e))?;
+
+    let chains = match input {
+        ChainlistInput::Many(chains) => chains,
+        ChainlistInput::One(chain) => vec![*chain],
+    };
+
+    let mut entries = Vec::new();
+    let mut report = ImportReport {
+        missing_chain_ids: chain_ids
+            .iter()
+            .filter(|id| !chains.iter().any(|c| c.chain_id == **id))
+            .copied()
+            .collect(),
+        ..Default::default()
+    };
+
+    for chain in chains {
+        if !chain_ids.is_empty() && !chain_ids.contains(&chain.chain_id) {
+            continue;
+        }
+
+        let name = slugify(&chain.name);
+
+        let Some(rpc_url) = chain
+            .rpc
+            .iter()
+            .map(|r| r.url())
+            .find(|url| url.starts_with("http") && !url.contains("${"))
+        else {
+            report
+                .skipped
+                .push((name, "no public HTTP RPC endpoint".to_string()));
+            continue;
+        };
+
+        let aliases = chain
+            .short_name
+            .map(|s| s.to_lowercase())
+            .filter(|s| !s.is_empty() && *s != name)
+            .into_iter()
+            .collect();
+
+        entries.push(RegistryEntry {
+            name,
+            aliases,
+            chain_id: chain.chain_id,
+            rpc_url: rpc_url.to_string(),
+            explorer_url: chain
+                .explorers
+                .first()
+                .map(|e| e.url.trim_end_matches('/').to_string()),
+            native_currency: chain.native_currency.map(|c| c.symbol),
+        });
+    }
+
+    Ok((entries, report))
+}
+
+/// Turns a display name like "Arbitrum One" into a network name like "arbitrum-one".
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETHEREUM_LISTS: &str = r#"[
+        {
+            "name": "Zora",
+            "chain": "ETH",
+            "rpc": ["https://rpc.zora.energy/", "wss://rpc.zora.energy"],
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+            "shortName": "zora",
+            "chainId": 7777777,
+            "explorers": [{ "name": "Zora", "url": "https://explorer.zora.energy/", "standard": "EIP3091" }]
+        },
+        {
+            "name": "Ethereum Mainnet",
+            "rpc": ["https://mainnet.infura.io/v3/${INFURA_API_KEY}", "https://api.mycryptoapi.com/eth"],
+            "shortName": "eth",
+            "chainId": 1
+        },
+        {
+            "name": "Keyed Only Chain",
+            "rpc": ["https://rpc.example.com/${API_KEY}"],
+            "shortName": "koc",
+            "chainId": 424242
+        }
+    ]"#;
+
+    // ==================== parse_chainlist() tests ====================
+
+    #[test]
+    fn parses_ethereum_lists_format() {
+        let (entries, report) = parse_chainlist(ETHEREUM_LISTS, &[]).unwrap();
+
+        let zora = entries.iter().find(|e| e.chain_id == 7777777).unwrap();
+        assert_eq!(zora.name, "zora");
+        assert!(zora.aliases.is_empty());
+        assert_eq!(zora.rpc_url, "https://rpc.zora.energy/");
+        assert_eq!(
+            zora.explorer_url,
+            Some("https://explorer.zora.energy".to_string())
+        );
+        assert_eq!(zora.native_currency, Some("ETH".to_string()));
+
+        assert!(report.missing_chain_ids.is_empty());
+    }
+
+    #[test]
+    fn skips_templated_rpc_urls() {
+        let (entries, report) = parse_chainlist(ETHEREUM_LISTS, &[]).unwrap();
+
+        let mainnet = entries.iter().find(|e| e.chain_id == 1).unwrap();
+        assert_eq!(mainnet.rpc_url, "https://api.mycryptoapi.com/eth");
+
+        assert!(!entries.iter().any(|e| e.chain_id == 424242));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, "keyed-only-chain");
+    }
+
+    #[test]
+    fn parses_chainlist_rpc_objects_and_single_chain() {
+        let json = r#"{
+            "name": "Mode Testnet",
+            "rpc": [{ "url": "https://sepolia.mode.network", "tracking": "none" }],
+            "shortName": "modesep",
+            "chainId": 919
+        }"#;
+
+        let (entries, _) = parse_chainlist(json, &[]).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "mode-testnet");
+        assert_eq!(entries[0].aliases, vec!["modesep".to_string()]);
+        assert_eq!(entries[0].rpc_url, "https://sepolia.mode.network");
+    }
+
+    #[test]
+    fn filters_by_chain_id_and_reports_missing() {
+        let (entries, report) = parse_chainlist(ETHEREUM_LISTS, &[7777777, 5]).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].chain_id, 7777777);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.missing_chain_ids, vec![5]);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(parse_chainlist("not json", &[]).is_err());
+    }
+
+    // ==================== Registry::import() tests ====================
+
+    fn entry(name: &str, chain_id: u64, aliases: &[&str]) -> RegistryEntry {
+        RegistryEntry {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            chain_id,
+            rpc_url: "https://rpc.example.com".to_string(),
+            explorer_url: None,
+            native_currency: None,
+        }
+    }
+
+    #[test]
+    fn import_skips_builtin_chain_ids() {
+        let mut registry = Registry::default();
+        let mut report = ImportReport::default();
+
+        registry.import(vec![entry("ethereum-mainnet", 1, &["eth"])], &mut report);
+
+        assert!(registry.networks.is_empty());
+        assert!(report.added.is_empty());
+        assert_eq!(report.skipped[0].1, "built-in network 'mainnet'");
+    }
+
+    #[test]
+    fn import_skips_already_imported_chains() {
+        let mut registry = Registry::default();
+        registry.networks.push(entry("zora", 7777777, &[]));
+        let mut report = ImportReport::default();
+
+        registry.import(vec![entry("zora-network", 7777777, &[])], &mut report);
+
+        assert_eq!(registry.networks.len(), 1);
+        assert_eq!(report.skipped[0].1, "already imported as 'zora'");
+    }
+
+    #[test]
+    fn import_skips_names_shadowing_builtins() {
+        let mut registry = Registry::default();
+        let mut report = ImportReport::default();
+
+        registry.import(vec![entry("arb", 999999, &[])], &mut report);
+
+        assert!(registry.networks.is_empty());
+        assert_eq!(report.skipped[0].1, "name 'arb' is already in use");
+    }
+
+    #[test]
+    fn import_drops_clashing_aliases() {
+        let mut registry = Registry::default();
+        let mut report = ImportReport::default();
+
+        registry.import(vec![entry("zora", 7777777, &["eth", "zr"])], &mut report);
+
+        assert_eq!(registry.networks[0].aliases, vec!["zr".to_string()]);
+        assert_eq!(report.added.len(), 1);
+    }
+
+    #[test]
+    fn registry_entry_becomes_network_with_custom_rpc() {
+        let network = entry("zora", 7777777, &["zr"]).to_network();
+
+        assert_eq!(network.name, "zora");
+        assert!(network.matches("zr"));
+        assert!(network.matches("7777777"));
+        assert_eq!(network.rpc_url("ignored-key"), "https://rpc.example.com");
+    }
+
+    #[test]
+    fn slugify_normalizes_display_names() {
+        assert_eq!(slugify("Arbitrum One"), "arbitrum-one");
+        assert_eq!(slugify("OP Mainnet"), "op-mainnet");
+        assert_eq!(slugify("  Zora (Mainnet) "), "zora-mainnet");
+    }
+}
@@ -0,0 +1,87 @@
+use crate::redact::redact_text;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends a JSON-RPC request without parameters and returns its `result`.
+pub fn call(rpc_url: &str, method: &str) -> Result<Value, String> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [] });
+
+    let response: Value = ureq::post(rpc_url)
+        .timeout(TIMEOUT)
+        .send_json(request)
+        .map_err(|e| format!("{} failed: {}", method, redact_text(&e.to_string())))?
+        .into_json()
+        .map_err(|e| format!("{} returned invalid JSON: {}", method, e))?;
+
+    result_of(response).map_err(|e| format!("{} failed: {}", method, e))
+}
+
+fn result_of(mut response: Value) -> Result<Value, String> {
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(message.to_string());
+    }
+
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err("response has no result".to_string()),
+    }
+}
+
+/// Parses a JSON-RPC hex quantity such as `"0x1b4"`.
+pub fn parse_quantity(value: &Value) -> Result<u128, String> {
+    let hex = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| format!("Expected a hex quantity, got {}", value))?;
+
+    u128::from_str_radix(hex, 16).map_err(|e| format!("Invalid hex quantity {}: {}", value, e))
+}
+
+/// Formats a wei amount as gwei with up to three decimals.
+pub fn format_gwei(wei: u128) -> String {
+    let gwei = wei / 1_000_000_000;
+    let millis = wei % 1_000_000_000 / 1_000_000;
+
+    if millis == 0 {
+        format!("{} gwei", gwei)
+    } else {
+        let decimals = format!("{:03}", millis);
+        format!("{}.{} gwei", gwei, decimals.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_result() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": "0x10" });
+        assert_eq!(result_of(response).unwrap(), json!("0x10"));
+    }
+
+    #[test]
+    fn surfaces_rpc_errors() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "Method not found" } });
+        assert_eq!(result_of(response).unwrap_err(), "Method not found");
+    }
+
+    #[test]
+    fn parses_hex_quantities() {
+        assert_eq!(parse_quantity(&json!("0x0")).unwrap(), 0);
+        assert_eq!(parse_quantity(&json!("0x1b4")).unwrap(), 436);
+        assert!(parse_quantity(&json!("1b4")).is_err());
+        assert!(parse_quantity(&json!(436)).is_err());
+    }
+
+    #[test]
+    fn formats_gas_prices_in_gwei() {
+        assert_eq!(format_gwei(30_000_000_000), "30 gwei");
+        assert_eq!(format_gwei(1_500_000_000), "1.5 gwei");
+        assert_eq!(format_gwei(12_345_000), "0.012 gwei");
+        assert_eq!(format_gwei(0), "0 gwei");
+    }
+}
@@ -0,0 +1,395 @@
+use crate::config::write_atomic;
+use crate::paths;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SESSION_VAR: &str = "STARGATE_SESSION";
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const SESSION_ID_LEN: usize = 8;
+/// How long an `unlock` lasts.
+pub const SESSION_TTL_SECS: u64 = 12 * 60 * 60;
+
+/// The plaintext stored inside the vault.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Secrets {
+    pub api_key: Option<String>,
+}
+
+/// Password-encrypted API key storage in `vault.toml` next to the config.
+///
+/// The password is stretched with Argon2id into a 256-bit key that encrypts the
+/// secrets with ChaCha20-Poly1305. `unlock` starts a [`Session`] so the password
+/// is asked once per shell. Byte fields are hex.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Vault {
+    /// Argon2id memory cost in KiB
+    m_cost: u32,
+    /// Argon2id iterations
+    t_cost: u32,
+    /// Argon2id parallelism
+    p_cost: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub type SessionKey = [u8; KEY_LEN];
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    Ok(bytes)
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value).map_err(|_| format!("Vault is corrupted: invalid {}", field))
+}
+
+impl Vault {
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|d| d.join("vault.toml"))
+    }
+
+    pub fn exists() -> bool {
+        Self::path().is_some_and(|p| p.exists())
+    }
+
+    /// Loads the vault, or `None` if no key has been encrypted yet.
+    pub fn load() -> Result<Option<Self>, String> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not determine home directory")?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize vault: {}", e))?;
+        write_atomic(&path, &content).map_err(|e| format!("Failed to write vault: {}", e))
+    }
+
+    /// Derives a key for a new vault with a fresh salt and default Argon2id costs.
+    pub fn create(password: &str) -> Result<(Self, SessionKey), String> {
+        let params = Params::default();
+        let mut vault = Self {
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+            salt: hex::encode(random_bytes::<SALT_LEN>()?),
+            nonce: String::new(),
+            ciphertext: String::new(),
+        };
+        let key = vault.derive_key(password)?;
+        vault.seal(&key, &Secrets::default())?;
+        Ok((vault, key))
+    }
+
+    /// Stretches `password` with this vault's salt and costs.
+    pub fn derive_key(&self, password: &str) -> Result<SessionKey, String> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(KEY_LEN))
+            .map_err(|e| format!("Vault has invalid KDF parameters: {}", e))?;
+        let salt = decode_hex("salt", &self.salt)?;
+
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+        Ok(key)
+    }
+
+    /// Encrypts `secrets` under `key` with a fresh nonce.
+    pub fn seal(&mut self, key: &SessionKey, secrets: &Secrets) -> Result<(), String> {
+        let plaintext =
+            toml::to_string(secrets).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+        let nonce = random_bytes::<NONCE_LEN>()?;
+
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|_| "Failed to encrypt vault".to_string())?;
+
+        self.nonce = hex::encode(nonce);
+        self.ciphertext = hex::encode(ciphertext);
+        Ok(())
+    }
+
+    /// Decrypts the secrets; fails on a wrong key or a tampered file.
+    pub fn open(&self, key: &SessionKey) -> Result<Secrets, String> {
+        let nonce = decode_hex("nonce", &self.nonce)?;
+        let ciphertext = decode_hex("ciphertext", &self.ciphertext)?;
+        if nonce.len() != NONCE_LEN {
+            return Err("Vault is corrupted: invalid nonce".to_string());
+        }
+
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(key))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| "Wrong password, or the vault has been modified.".to_string())?;
+
+        let plaintext = String::from_utf8(plaintext)
+            .map_err(|_| "Vault is corrupted: secrets are not UTF-8".to_string())?;
+        toml::from_str(&plaintext).map_err(|e| format!("Vault is corrupted: {}", e))
+    }
+}
+
+/// An unlocked vault (`sessions/<id>.toml` in the state directory): the vault key
+/// wrapped under a random per-session key. `STARGATE_SESSION` holds only the id and
+/// that wrapping key, so neither the variable nor the file opens the vault alone,
+/// and the session ends at `expires` or when `lock` removes the file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    /// Unix timestamp; authenticated along with the wrapped key
+    expires: u64,
+    nonce: String,
+    wrapped_key: String,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Session {
+    fn wrap(key: &SessionKey, wrapping_key: &SessionKey, expires: u64) -> Result<Self, String> {
+        let nonce = random_bytes::<NONCE_LEN>()?;
+        let aad = expires.to_be_bytes();
+        let payload = Payload {
+            msg: key,
+            aad: &aad,
+        };
+        let wrapped_key = ChaCha20Poly1305::new(Key::from_slice(wrapping_key))
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| "Failed to encrypt vault session".to_string())?;
+
+        Ok(Self {
+            expires,
+            nonce: hex::encode(nonce),
+            wrapped_key: hex::encode(wrapped_key),
+        })
+    }
+
+    fn unwrap(&self, wrapping_key: &SessionKey, now: u64) -> Result<SessionKey, String> {
+        const INVALID: &str = "Vault session is invalid. Run 'sg unlock' again.";
+        if now >= self.expires {
+            return Err("Vault session has expired. Run 'sg unlock' again.".to_string());
+        }
+
+        let nonce = hex::decode(&self.nonce).map_err(|_| INVALID)?;
+        let wrapped_key = hex::decode(&self.wrapped_key).map_err(|_| INVALID)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(INVALID.to_string());
+        }
+        let aad = self.expires.to_be_bytes();
+        let payload = Payload {
+            msg: &wrapped_key,
+            aad: &aad,
+        };
+        let key = ChaCha20Poly1305::new(Key::from_slice(wrapping_key))
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| INVALID)?;
+        key.try_into().map_err(|_| INVALID.to_string())
+    }
+
+    fn dir() -> Option<PathBuf> {
+        paths::state_dir().map(|d| d.join("sessions"))
+    }
+
+    fn path(id: &str) -> Option<PathBuf> {
+        Self::dir().map(|d| d.join(format!("{}.toml", id)))
+    }
+
+    /// Removes expired and unreadable sessions left behind by shells that never locked.
+    fn prune(now: u64) {
+        let Some(entries) = Self::dir().and_then(|d| fs::read_dir(d).ok()) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let expired = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| toml::from_str::<Session>(&content).ok())
+                .is_none_or(|session| now >= session.expires);
+            if expired {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+/// `STARGATE_SESSION` is `<id>.<wrapping key>`, both hex.
+fn parse_token(token: &str) -> Option<(String, SessionKey)> {
+    let (id, wrapping_key) = token.trim().split_once('.')?;
+    let valid_id = id.len() == SESSION_ID_LEN * 2 && id.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid_id {
+        return None;
+    }
+    let wrapping_key = hex::decode(wrapping_key).ok()?.try_into().ok()?;
+    Some((id.to_lowercase(), wrapping_key))
+}
+
+/// Stores `key` for a new session and returns the token for `STARGATE_SESSION`.
+pub fn start_session(key: &SessionKey) -> Result<String, String> {
+    let now = now();
+    Session::prune(now);
+
+    let id = hex::encode(random_bytes::<SESSION_ID_LEN>()?);
+    let wrapping_key = random_bytes::<KEY_LEN>()?;
+    let session = Session::wrap(key, &wrapping_key, now + SESSION_TTL_SECS)?;
+
+    let path = Session::path(&id).ok_or("Could not determine home directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create state directory: {}", e))?;
+    }
+    let content = toml::to_string(&session)
+        .map_err(|e| format!("Failed to serialize vault session: {}", e))?;
+    write_atomic(&path, &content).map_err(|e| format!("Failed to write vault session: {}", e))?;
+
+    Ok(format!("{}.{}", id, hex::encode(wrapping_key)))
+}
+
+/// Removes this shell's session file, if any, so its token stops working.
+pub fn end_session() {
+    let Some((id, _)) = env::var(SESSION_VAR).ok().as_deref().and_then(parse_token) else {
+        return;
+    };
+    if let Some(path) = Session::path(&id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// The vault key of this shell's session. Fails when the shell isn't unlocked,
+/// or the session has expired or been locked.
+fn session_key() -> Result<SessionKey, String> {
+    let token = env::var(SESSION_VAR)
+        .map_err(|_| "API key is encrypted. Run 'sg unlock' to unlock it for this shell.")?;
+    let (id, wrapping_key) =
+        parse_token(&token).ok_or("Vault session is invalid. Run 'sg unlock' again.")?;
+
+    let session = Session::path(&id)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str::<Session>(&content).ok())
+        .ok_or("Vault session has expired or was locked. Run 'sg unlock' again.")?;
+    session.unwrap(&wrapping_key, now())
+}
+
+/// The API key stored in the vault, if there is a vault. Fails when the vault
+/// exists but this shell hasn't unlocked it.
+pub fn api_key() -> Result<Option<String>, String> {
+    let Some(vault) = Vault::load()? else {
+        return Ok(None);
+    };
+    Ok(vault.open(&session_key()?)?.api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault() -> Vault {
+        Vault {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            salt: hex::encode([7u8; SALT_LEN]),
+            nonce: String::new(),
+            ciphertext: String::new(),
+        }
+    }
+
+    #[test]
+    fn seal_and_open_roundtrip() {
+        let mut vault = vault();
+        let key = vault.derive_key("hunter2").unwrap();
+        let secrets = Secrets {
+            api_key: Some("alchemy-key".to_string()),
+        };
+        vault.seal(&key, &secrets).unwrap();
+
+        assert!(!vault.ciphertext.contains(&hex::encode("alchemy-key")));
+        let opened = vault.open(&key).unwrap();
+        assert_eq!(opened.api_key.as_deref(), Some("alchemy-key"));
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let mut vault = vault();
+        let key = vault.derive_key("hunter2").unwrap();
+        vault.seal(&key, &Secrets::default()).unwrap();
+
+        let wrong = vault.derive_key("hunter3").unwrap();
+        assert!(vault.open(&wrong).is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let mut vault = vault();
+        let key = vault.derive_key("hunter2").unwrap();
+        vault.seal(&key, &Secrets::default()).unwrap();
+
+        let mut bytes = hex::decode(&vault.ciphertext).unwrap();
+        bytes[0] ^= 1;
+        vault.ciphertext = hex::encode(bytes);
+        assert!(vault.open(&key).is_err());
+    }
+
+    #[test]
+    fn session_unwraps_the_vault_key_until_it_expires() {
+        let key = [42u8; KEY_LEN];
+        let wrapping_key = [7u8; KEY_LEN];
+        let session = Session::wrap(&key, &wrapping_key, 1000).unwrap();
+
+        assert!(!session.wrapped_key.contains(&hex::encode(key)));
+        assert_eq!(session.unwrap(&wrapping_key, 999).unwrap(), key);
+        assert!(session.unwrap(&wrapping_key, 1000).is_err());
+        assert!(session.unwrap(&[8u8; KEY_LEN], 999).is_err());
+    }
+
+    #[test]
+    fn extending_a_session_breaks_it() {
+        let wrapping_key = [7u8; KEY_LEN];
+        let mut session = Session::wrap(&[42u8; KEY_LEN], &wrapping_key, 1000).unwrap();
+        session.expires = u64::MAX;
+        assert!(session.unwrap(&wrapping_key, 999).is_err());
+    }
+
+    #[test]
+    fn session_token_holds_id_and_wrapping_key() {
+        let token = format!(
+            "{}.{}",
+            "ab".repeat(SESSION_ID_LEN),
+            hex::encode([7u8; KEY_LEN])
+        );
+        let (id, wrapping_key) = parse_token(&token).unwrap();
+        assert_eq!(id, "ab".repeat(SESSION_ID_LEN));
+        assert_eq!(wrapping_key, [7u8; KEY_LEN]);
+
+        // The old format exported the vault key itself
+        assert!(parse_token(&hex::encode([42u8; KEY_LEN])).is_none());
+        assert!(parse_token("../../x.00").is_none());
+    }
+}